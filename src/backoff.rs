@@ -0,0 +1,384 @@
+// Retry backoff schedules like "100ms*2^n max 30s"
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::duration::{duration, parse_duration};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidBackoff,
+    InvalidFactor,
+    Duration(crate::duration::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidBackoff => write!(f, "invalid backoff"),
+            Error::InvalidFactor => write!(f, "invalid factor, must be at least 1"),
+            Error::Duration(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<crate::duration::Error> for Error {
+    fn from(err: crate::duration::Error) -> Self {
+        Self::Duration(err)
+    }
+}
+
+/// Backoff is the delay between retries, growing with the attempt number.
+/// Delays saturate at `max`, or at `Duration::MAX` instead of overflowing.
+///
+/// See also: `parse_backoff`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// Constant waits the same delay every time, "constant 5s".
+    Constant(Duration),
+    /// Linear adds `step` after every attempt, "linear 1s+1s max 10s".
+    Linear {
+        initial: Duration,
+        step: Duration,
+        max: Option<Duration>,
+    },
+    /// Exponential multiplies by `factor` after every attempt,
+    /// "100ms*2^n max 30s".
+    Exponential {
+        initial: Duration,
+        factor: f64,
+        max: Option<Duration>,
+    },
+}
+
+impl Backoff {
+    /// delay returns the delay before retry `n`, starting at 0.
+    ///
+    /// parse_backoff("100ms*2^n max 30s")?.delay(3) -> 800ms
+    #[must_use]
+    pub fn delay(&self, n: u32) -> Duration {
+        match *self {
+            Backoff::Constant(d) => d,
+            Backoff::Linear { initial, step, max } => {
+                let d = step
+                    .checked_mul(n)
+                    .and_then(|step| initial.checked_add(step));
+                cap(d, max)
+            }
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                // Zero and a factor of 1 never grow, even where factor^n overflows.
+                // Otherwise factor^n overflowing means the product does too.
+                // Whole factors are exact, others go through f64 seconds
+                let d = if initial.is_zero() || factor == 1.0 {
+                    Some(initial)
+                } else if factor.fract() == 0.0 && factor <= f64::from(u32::MAX) {
+                    (factor as u128)
+                        .checked_pow(n)
+                        .and_then(|factor| initial.as_nanos().checked_mul(factor))
+                        .and_then(from_nanos)
+                } else {
+                    let factor = factor.powi(i32::try_from(n).unwrap_or(i32::MAX));
+                    Duration::try_from_secs_f64(initial.as_secs_f64() * factor).ok()
+                };
+                cap(d, max)
+            }
+        }
+    }
+
+    /// iter returns the delays before each retry, it never ends.
+    #[must_use]
+    pub fn iter(&self) -> Delays {
+        Delays {
+            backoff: *self,
+            attempt: 0,
+        }
+    }
+}
+
+impl IntoIterator for Backoff {
+    type Item = Duration;
+    type IntoIter = Delays;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Delays is the iterator returned by `Backoff::iter`.
+#[derive(Clone, Debug)]
+pub struct Delays {
+    backoff: Backoff,
+    attempt: u32,
+}
+
+impl Iterator for Delays {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let d = self.backoff.delay(self.attempt);
+        self.attempt = self.attempt.saturating_add(1);
+        Some(d)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (usize::MAX, None)
+    }
+}
+
+fn cap(d: Option<Duration>, max: Option<Duration>) -> Duration {
+    let d = d.unwrap_or(Duration::MAX);
+    max.map_or(d, |max| d.min(max))
+}
+
+fn from_nanos(nanos: u128) -> Option<Duration> {
+    let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+}
+
+/// Formats the backoff in the canonical syntax of `parse_backoff`, with
+/// durations from `duration`.
+impl Display for Backoff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let max = match self {
+            Backoff::Constant(d) => return write!(f, "constant {}", duration(d)),
+            Backoff::Linear { initial, step, max } => {
+                write!(f, "linear {}+{}", duration(initial), duration(step))?;
+                max
+            }
+            Backoff::Exponential {
+                initial,
+                factor,
+                max,
+            } => {
+                write!(f, "{}*{}^n", duration(initial), factor)?;
+                max
+            }
+        };
+
+        match max {
+            Some(max) => write!(f, " max {}", duration(max)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// parse_backoff parses a backoff schedule with durations accepted by
+/// `parse_duration`:
+///
+///   constant 5s
+///   linear 1s+1s max 10s
+///   100ms*2^n max 30s
+///
+/// The function forms "constant(5s)", "linear(1s, 1s, max=10s)" and
+/// "exp(100ms, 2x, max=30s)" are accepted too. "max" is optional, and the
+/// factor of an exponential backoff is at least 1.
+///
+/// # Errors
+///
+/// Return `Error::Duration` if a duration is not valid, `Error::InvalidFactor`
+/// for a bad factor, or `Error::InvalidBackoff` if the input is malformed.
+pub fn parse_backoff(text: &str) -> Result<Backoff, Error> {
+    let text = text.trim();
+
+    if let Some(args) = text.strip_suffix(')') {
+        let (name, args) = args.split_once('(').ok_or(Error::InvalidBackoff)?;
+        let mut args: Vec<&str> = args.split(',').map(str::trim).collect();
+        let max = match args.last().and_then(|arg| arg.strip_prefix("max")) {
+            Some(max) => {
+                let max = max
+                    .trim_start()
+                    .strip_prefix('=')
+                    .ok_or(Error::InvalidBackoff)?;
+                args.pop();
+                Some(parse_duration(max.trim())?)
+            }
+            None => None,
+        };
+
+        return match (name.trim(), args.as_slice()) {
+            ("constant", [d]) if max.is_none() => Ok(Backoff::Constant(parse_duration(d)?)),
+            ("linear", [initial, step]) => Ok(Backoff::Linear {
+                initial: parse_duration(initial)?,
+                step: parse_duration(step)?,
+                max,
+            }),
+            ("exp", [initial, factor]) => Ok(Backoff::Exponential {
+                initial: parse_duration(initial)?,
+                factor: parse_factor(factor.strip_suffix('x').unwrap_or(factor))?,
+                max,
+            }),
+            _ => Err(Error::InvalidBackoff),
+        };
+    }
+
+    if let Some(d) = text.strip_prefix("constant ") {
+        return Ok(Backoff::Constant(parse_duration(d.trim())?));
+    }
+
+    let (text, max) = match text.rsplit_once(" max ") {
+        Some((text, max)) => (text.trim(), Some(parse_duration(max.trim())?)),
+        None => (text, None),
+    };
+
+    if let Some(steps) = text.strip_prefix("linear ") {
+        let (initial, step) = steps.split_once('+').ok_or(Error::InvalidBackoff)?;
+        return Ok(Backoff::Linear {
+            initial: parse_duration(initial.trim())?,
+            step: parse_duration(step.trim())?,
+            max,
+        });
+    }
+
+    let power = text.strip_suffix("^n").ok_or(Error::InvalidBackoff)?;
+    let (initial, factor) = power.rsplit_once('*').ok_or(Error::InvalidBackoff)?;
+    Ok(Backoff::Exponential {
+        initial: parse_duration(initial.trim())?,
+        factor: parse_factor(factor.trim())?,
+        max,
+    })
+}
+
+fn parse_factor(factor: &str) -> Result<f64, Error> {
+    match factor.parse::<f64>() {
+        Ok(factor) if factor.is_finite() && factor >= 1.0 => Ok(factor),
+        _ => Err(Error::InvalidFactor),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+    const SEC: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn parse() {
+        let tests = [
+            ("constant 5s", Backoff::Constant(5 * SEC), "constant 5s"),
+            ("constant(5s)", Backoff::Constant(5 * SEC), "constant 5s"),
+            (
+                "linear 1s+500ms max 10s",
+                Backoff::Linear {
+                    initial: SEC,
+                    step: 500 * MS,
+                    max: Some(10 * SEC),
+                },
+                "linear 1s+500ms max 10s",
+            ),
+            (
+                "linear(1s, 1s)",
+                Backoff::Linear {
+                    initial: SEC,
+                    step: SEC,
+                    max: None,
+                },
+                "linear 1s+1s",
+            ),
+            (
+                "100ms*2^n max 30s",
+                Backoff::Exponential {
+                    initial: 100 * MS,
+                    factor: 2.0,
+                    max: Some(30 * SEC),
+                },
+                "100ms*2^n max 30s",
+            ),
+            (
+                "exp(100ms, 1.5x, max=1m)",
+                Backoff::Exponential {
+                    initial: 100 * MS,
+                    factor: 1.5,
+                    max: Some(60 * SEC),
+                },
+                "100ms*1.5^n max 1m",
+            ),
+        ];
+
+        for (input, backoff, want) in tests {
+            assert_eq!(parse_backoff(input), Ok(backoff), "input: {input}");
+            assert_eq!(backoff.to_string(), want, "input: {input}");
+            assert_eq!(parse_backoff(want), Ok(backoff), "input: {input}");
+        }
+
+        let tests = [
+            ("", Error::InvalidBackoff),
+            ("constant", Error::InvalidBackoff),
+            ("constant(5s, max=1m)", Error::InvalidBackoff),
+            ("linear 1s", Error::InvalidBackoff),
+            ("100ms*2", Error::InvalidBackoff),
+            ("exp(100ms, 2x, 3x)", Error::InvalidBackoff),
+            ("exp(100ms, 2x, max 1s)", Error::InvalidBackoff),
+            ("100ms*0.5^n", Error::InvalidFactor),
+            ("100ms*x^n", Error::InvalidFactor),
+            ("exp(100ms, infx)", Error::InvalidFactor),
+            (
+                "100*2^n",
+                Error::Duration(crate::duration::Error::MissingUnit),
+            ),
+        ];
+        for (input, want) in tests {
+            assert_eq!(parse_backoff(input), Err(want), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn delays() {
+        let delays =
+            |text: &str| -> Vec<Duration> { parse_backoff(text).unwrap().iter().take(6).collect() };
+
+        assert_eq!(delays("constant 5s"), vec![5 * SEC; 6]);
+        assert_eq!(
+            delays("linear 1s+1s max 4s"),
+            [1, 2, 3, 4, 4, 4].map(|s| s * SEC)
+        );
+        assert_eq!(
+            delays("100ms*2^n max 2s"),
+            [100, 200, 400, 800, 1600, 2000].map(|ms| ms * MS)
+        );
+        assert_eq!(
+            delays("1s*1.5^n"),
+            [1000.0, 1500.0, 2250.0, 3375.0, 5062.5, 7593.75].map(|ms| MS.mul_f64(ms))
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        let backoff = parse_backoff("1s*2^n").unwrap();
+        assert_eq!(backoff.delay(63), Duration::from_secs(1 << 63));
+        assert_eq!(backoff.delay(64), Duration::MAX);
+        assert_eq!(backoff.delay(u32::MAX), Duration::MAX);
+
+        let backoff = parse_backoff("0s*2^n").unwrap();
+        assert_eq!(backoff.delay(200), Duration::ZERO);
+        assert_eq!(backoff.delay(u32::MAX), Duration::ZERO);
+
+        let backoff = parse_backoff("0s*1.5^n").unwrap();
+        assert_eq!(backoff.delay(u32::MAX), Duration::ZERO);
+
+        let backoff = parse_backoff("1s*1^n").unwrap();
+        assert_eq!(backoff.delay(u32::MAX), SEC);
+
+        let backoff = parse_backoff("1ns*2^n").unwrap();
+        assert_eq!(backoff.delay(127), Duration::MAX);
+        assert_eq!(backoff.delay(200), Duration::MAX);
+
+        let backoff = parse_backoff("1s*1.5^n max 1h").unwrap();
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(3600));
+
+        let backoff = parse_backoff("linear 1s+5124095576030431h max 1h").unwrap();
+        assert_eq!(backoff.delay(2), Duration::from_secs(3600));
+        assert_eq!(backoff.delay(u32::MAX), Duration::from_secs(3600));
+
+        let mut delays = parse_backoff("constant 1s").unwrap().iter();
+        assert_eq!(delays.size_hint(), (usize::MAX, None));
+        assert_eq!(delays.nth(1_000_000), Some(SEC));
+    }
+}
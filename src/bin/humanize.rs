@@ -0,0 +1,410 @@
+// humanize converts numbers from/to human-readable strings in shell pipelines.
+
+use std::io::{BufRead, BufWriter, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use humanize::bytes::{bytes, ibytes, parse_bytes};
+use humanize::duration::{duration, parse_duration};
+
+const USAGE: &str = "\
+Usage: humanize COMMAND [OPTION]... [VALUE]...
+
+Convert VALUEs, or the fields of the lines of standard input if no VALUE
+is given, from/to human-readable strings.
+
+Commands:
+  bytes           format byte counts with SI units, e.g. 1000 -> 1kB
+  ibytes          format byte counts with IEC units, e.g. 1024 -> 1KiB
+  parse-bytes     parse sizes into byte counts, e.g. 1KiB -> 1024
+  duration        normalize durations, or format nanoseconds, e.g. 90m -> 1h30m
+  parse-duration  parse durations into nanoseconds, e.g. 1ms -> 1000000
+
+Options:
+  -d, --delimiter=X  use X instead of whitespace as the field delimiter
+      --field=FIELDS  convert these input fields, default is 1, e.g. 2,4-6 or -
+      --header[=N]    print the first N (default 1) input lines unconverted
+  -h, --help          display this help and exit
+";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Command {
+    Bytes,
+    IBytes,
+    ParseBytes,
+    Duration,
+    ParseDuration,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bytes" => Some(Command::Bytes),
+            "ibytes" => Some(Command::IBytes),
+            "parse-bytes" => Some(Command::ParseBytes),
+            "duration" => Some(Command::Duration),
+            "parse-duration" => Some(Command::ParseDuration),
+            _ => None,
+        }
+    }
+
+    fn convert(self, input: &str) -> Result<String, String> {
+        let size = || {
+            input
+                .parse::<usize>()
+                .map_err(|_| format!("invalid byte count: '{}'", input))
+        };
+
+        match self {
+            Command::Bytes => Ok(bytes(size()?)),
+            Command::IBytes => Ok(ibytes(size()?)),
+            Command::ParseBytes => parse_bytes(input)
+                .map(|size| size.to_string())
+                .map_err(|err| format!("invalid size '{}': {}", input, err)),
+            // A plain integer is a number of nanoseconds, both print through
+            // `duration` so the output is the same
+            Command::Duration => {
+                let d = match input.parse::<i64>() {
+                    Ok(nanos) => Ok((nanos < 0, Duration::from_nanos(nanos.unsigned_abs()))),
+                    Err(_) => parse_signed_duration(input),
+                };
+                d.map(|(neg, d)| format!("{}{}", sign(neg), duration(&d)))
+            }
+            Command::ParseDuration => parse_signed_duration(input)
+                .map(|(neg, d)| format!("{}{}", sign(neg), d.as_nanos())),
+        }
+    }
+}
+
+/// parse_signed_duration parses a possibly negative duration, `parse_duration`
+/// only accepts positive ones. It returns whether the duration is negative.
+fn parse_signed_duration(input: &str) -> Result<(bool, Duration), String> {
+    let (neg, text) = match input.strip_prefix('-') {
+        Some(text) if text.starts_with(|c: char| c.is_ascii_digit() || c == '.') => (true, text),
+        _ => (false, input),
+    };
+
+    match parse_duration(text) {
+        Ok(d) => Ok((neg && !d.is_zero(), d)),
+        Err(err) => Err(format!("invalid duration '{}': {}", input, err)),
+    }
+}
+
+fn sign(neg: bool) -> &'static str {
+    if neg { "-" } else { "" }
+}
+
+/// Fields is a list of inclusive 1-based field ranges, like `cut -f`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Fields(Vec<(usize, usize)>);
+
+impl Fields {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid field value: '{}'", spec);
+        let field = |s: &str| match s.parse::<usize>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(invalid()),
+        };
+
+        let mut ranges = Vec::new();
+        for part in spec.split(',') {
+            let range = match part.split_once('-') {
+                None => (field(part)?, field(part)?),
+                Some(("", "")) => (1, usize::MAX),
+                Some(("", end)) => (1, field(end)?),
+                Some((start, "")) => (field(start)?, usize::MAX),
+                Some((start, end)) => (field(start)?, field(end)?),
+            };
+            if range.0 > range.1 {
+                return Err(invalid());
+            }
+
+            ranges.push(range);
+        }
+
+        Ok(Fields(ranges))
+    }
+
+    fn contains(&self, field: usize) -> bool {
+        self.0
+            .iter()
+            .any(|(start, end)| (*start..=*end).contains(&field))
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    fields: Fields,
+    delimiter: Option<char>,
+    header: usize,
+    values: Vec<String>,
+}
+
+/// parse_args parses the command line, it returns None if help is requested.
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut args = args.into_iter();
+    let mut command = None;
+    let mut fields = Fields(vec![(1, 1)]);
+    let mut delimiter = None;
+    let mut header = 0;
+    let mut values = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let (name, inline) = match arg.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => (name, Some(value.to_string())),
+            _ => (arg.as_str(), None),
+        };
+        let mut value = |option: &str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("option requires an argument -- '{}'", option))
+        };
+
+        match name {
+            "-h" | "--help" => return Ok(None),
+            "-d" | "--delimiter" => {
+                let value = value("delimiter")?;
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => delimiter = Some(c),
+                    _ => return Err("the delimiter must be a single character".to_string()),
+                }
+            }
+            "--field" => fields = Fields::parse(&value("field")?)?,
+            "--header" => {
+                header = match &inline {
+                    Some(n) => n
+                        .parse()
+                        .map_err(|_| format!("invalid header value: '{}'", n))?,
+                    None => 1,
+                }
+            }
+            "--" => values.extend(args.by_ref()),
+            _ if name.starts_with('-') && name.len() > 1 && command.is_some() => {
+                // Negative durations, e.g. "-1h30m"
+                if name.starts_with("--") || !name[1..].starts_with(|c: char| c.is_ascii_digit()) {
+                    return Err(format!("unrecognized option '{}'", arg));
+                }
+                values.push(arg);
+            }
+            _ if name.starts_with('-') && name.len() > 1 => {
+                return Err(format!("unrecognized option '{}'", arg));
+            }
+            _ if command.is_none() => {
+                command = Some(
+                    Command::from_name(&arg).ok_or_else(|| format!("unknown command '{}'", arg))?,
+                );
+            }
+            _ => values.push(arg),
+        }
+    }
+
+    let command = command.ok_or_else(|| "missing command".to_string())?;
+
+    Ok(Some(Options {
+        command,
+        fields,
+        delimiter,
+        header,
+        values,
+    }))
+}
+
+/// convert_line converts the selected fields of a line, and keeps everything
+/// else as it is.
+fn convert_line(options: &Options, line: &str) -> Result<String, String> {
+    let mut out = String::with_capacity(line.len());
+
+    match options.delimiter {
+        Some(delimiter) => {
+            for (i, field) in line.split(delimiter).enumerate() {
+                if i > 0 {
+                    out.push(delimiter);
+                }
+                if options.fields.contains(i + 1) {
+                    out.push_str(&options.command.convert(field)?);
+                } else {
+                    out.push_str(field);
+                }
+            }
+        }
+        None => {
+            let mut field = 0;
+            let mut remain = line;
+            while !remain.is_empty() {
+                let blanks = remain.len() - remain.trim_start().len();
+                out.push_str(&remain[..blanks]);
+                remain = &remain[blanks..];
+
+                let end = remain.find(char::is_whitespace).unwrap_or(remain.len());
+                if end == 0 {
+                    break;
+                }
+
+                field += 1;
+                if options.fields.contains(field) {
+                    out.push_str(&options.command.convert(&remain[..end])?);
+                } else {
+                    out.push_str(&remain[..end]);
+                }
+                remain = &remain[end..];
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn run(options: &Options) -> Result<(), String> {
+    let stdout = std::io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let write_err = |err: std::io::Error| format!("write error: {}", err);
+
+    if !options.values.is_empty() {
+        for value in &options.values {
+            writeln!(out, "{}", options.command.convert(value)?).map_err(write_err)?;
+        }
+
+        return out.flush().map_err(write_err);
+    }
+
+    for (i, line) in std::io::stdin().lock().lines().enumerate() {
+        let line = line.map_err(|err| format!("read error: {}", err))?;
+        if i < options.header {
+            writeln!(out, "{}", line).map_err(write_err)?;
+        } else {
+            writeln!(out, "{}", convert_line(options, &line)?).map_err(write_err)?;
+        }
+    }
+
+    out.flush().map_err(write_err)
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("humanize: {}", err);
+            eprintln!("Try 'humanize --help' for more information.");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("humanize: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(args: &[&str]) -> Options {
+        parse_args(args.iter().map(|s| s.to_string()))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn args() {
+        let got = options(&["parse-bytes", "--field=2-3,5", "-d", ":", "--header", "1K"]);
+        assert_eq!(got.command, Command::ParseBytes);
+        assert_eq!(got.fields, Fields(vec![(2, 3), (5, 5)]));
+        assert_eq!(got.delimiter, Some(':'));
+        assert_eq!(got.header, 1);
+        assert_eq!(got.values, ["1K"]);
+
+        let got = options(&["duration", "--header=2", "--field", "-", "-1h", "--", "--x"]);
+        assert_eq!(got.fields, Fields(vec![(1, usize::MAX)]));
+        assert_eq!(got.header, 2);
+        assert_eq!(got.values, ["-1h", "--x"]);
+
+        assert!(parse_args(["--help".to_string()]).unwrap().is_none());
+        for args in [
+            &[][..],
+            &["bits"],
+            &["bytes", "--field=0"],
+            &["bytes", "--field=3-2"],
+            &["bytes", "--field"],
+            &["bytes", "-d", "::"],
+            &["bytes", "--fields=1"],
+            &["bytes", "-x"],
+        ] {
+            let got = parse_args(args.iter().map(|s| s.to_string()));
+            assert!(got.is_err(), "args: {args:?}");
+        }
+    }
+
+    #[test]
+    fn convert() {
+        let tests = [
+            (Command::Bytes, "82854982", "82.9MB"),
+            (Command::IBytes, "82854982", "79MiB"),
+            (Command::ParseBytes, "42 MB", "42000000"),
+            (Command::ParseBytes, "1KiB", "1024"),
+            (Command::Duration, "90m", "1h30m"),
+            (Command::Duration, "1500000000", "1.5s"),
+            (Command::Duration, "-1500000000", "-1.5s"),
+            (Command::Duration, "90061000000000", "1d1h1m1s"),
+            (Command::Duration, "90061s", "1d1h1m1s"),
+            (
+                Command::Duration,
+                "-9223372036854775808",
+                "-15250w1d23h47m16.854775808s",
+            ),
+            (Command::Duration, "300000h", "1785w5d"),
+            (Command::Duration, "-1h30m", "-1h30m"),
+            (Command::Duration, "-90m", "-1h30m"),
+            (Command::Duration, "-0s", "0s"),
+            (
+                Command::Duration,
+                "-5124095576030431h",
+                "-30500568904943w7h",
+            ),
+            (Command::ParseDuration, "1ms", "1000000"),
+            (Command::ParseDuration, "-1ms", "-1000000"),
+            (Command::ParseDuration, "+1ms", "1000000"),
+        ];
+
+        for (command, input, want) in tests {
+            assert_eq!(command.convert(input).unwrap(), want, "input: {input}");
+        }
+
+        assert!(Command::Bytes.convert("1K").is_err());
+        assert!(Command::ParseBytes.convert("1 XB").is_err());
+        assert!(Command::ParseDuration.convert("1").is_err());
+        assert!(Command::Duration.convert("-+1s").is_err());
+        assert!(Command::Duration.convert("--1s").is_err());
+
+        // Negative durations are values, not options
+        let got = options(&["duration", "-1h30m"]);
+        assert_eq!(got.command.convert(&got.values[0]).unwrap(), "-1h30m");
+    }
+
+    #[test]
+    fn lines() {
+        let got = convert_line(&options(&["ibytes"]), "  1024  /var/log").unwrap();
+        assert_eq!(got, "  1KiB  /var/log");
+
+        let got = convert_line(&options(&["parse-bytes", "--field=2-"]), "a 1K\t2K ").unwrap();
+        assert_eq!(got, "a 1000\t2000 ");
+
+        let got = convert_line(&options(&["bytes", "-d", ",", "--field=2"]), "x,1000,").unwrap();
+        assert_eq!(got, "x,1kB,");
+
+        let got = convert_line(&options(&["duration", "--field=2"]), "job -90m").unwrap();
+        assert_eq!(got, "job -1h30m");
+
+        assert!(convert_line(&options(&["bytes"]), "x 1000").is_err());
+    }
+}
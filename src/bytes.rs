@@ -1,5 +1,10 @@
+use std::cmp::Ordering;
 use std::fmt::{Display, Formatter};
 use std::num::ParseFloatError;
+use std::ops::{Bound, RangeBounds};
+
+#[cfg(feature = "numfmt")]
+pub mod numfmt;
 
 // ICE Sizes, kibis of bits
 const BYTE: usize = 1;
@@ -20,10 +25,15 @@ const PBYTE: usize = TBYTE * 1000;
 const EBYTE: usize = PBYTE * 1000;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error<'a> {
     ParseFloat(ParseFloatError),
     UnknownUnit { unit: &'a str },
     TooLarge { input: &'a str },
+    OutOfBounds(OutOfBounds),
+    InvalidRange { input: &'a str },
+    InvalidRate { input: &'a str },
+    InvalidPercent { input: &'a str },
 }
 
 impl<'a> std::error::Error for Error<'a> {}
@@ -36,6 +46,22 @@ impl<'a> Display for Error<'a> {
             }
             Error::UnknownUnit { unit } => write!(f, "unknown unit \"{}\"", unit),
             Error::TooLarge { input } => write!(f, "too large \"{}\"", input),
+            Error::OutOfBounds(err) => write!(f, "{}", err),
+            Error::InvalidRange { input } => write!(f, "invalid range \"{}\"", input),
+            Error::InvalidRate { input } => {
+                write!(
+                    f,
+                    "invalid rate \"{}\", expected SIZE/PERIOD of at least 1B/s",
+                    input
+                )
+            }
+            Error::InvalidPercent { input } => {
+                write!(
+                    f,
+                    "invalid percentage \"{}\", expected a plain decimal like 12.5%",
+                    input
+                )
+            }
         }
     }
 }
@@ -46,6 +72,12 @@ impl<'a> From<ParseFloatError> for Error<'a> {
     }
 }
 
+impl<'a> From<OutOfBounds> for Error<'a> {
+    fn from(err: OutOfBounds) -> Self {
+        Self::OutOfBounds(err)
+    }
+}
+
 /// bytes produces a human-readable representation of an SI size
 ///
 /// See also: `parse_bytes`
@@ -53,7 +85,7 @@ impl<'a> From<ParseFloatError> for Error<'a> {
 /// bytes(82854982) -> 83 MB
 #[must_use]
 pub fn bytes(s: usize) -> String {
-    humanate_bytes(s, 1000.0, ["B", "kB", "MB", "GB", "TB", "PB", "EB"])
+    humanate_bytes(s as u128, 1000.0, SI_NAMES)
 }
 
 /// ibytes produces a human-readable representation of an IEC size.
@@ -61,54 +93,1032 @@ pub fn bytes(s: usize) -> String {
 /// ibytes((82854982) -> 79 MiB
 #[must_use]
 pub fn ibytes(s: usize) -> String {
-    humanate_bytes(s, 1024.0, ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"])
+    humanate_bytes(s as u128, 1024.0, IEC_NAMES)
+}
+
+/// `exact_bytes` produces a lossless representation of an SI size, using the
+/// largest unit which divides it evenly.
+///
+/// exact_bytes(42000000) -> 42MB
+/// exact_bytes(1500) -> 1500B
+#[must_use]
+pub fn exact_bytes(s: usize) -> String {
+    exact_units(s, 1000, SI_NAMES)
+}
+
+/// `exact_ibytes` produces a lossless representation of an IEC size, using the
+/// largest unit which divides it evenly.
+///
+/// exact_ibytes(44564480) -> 43520KiB
+#[must_use]
+pub fn exact_ibytes(s: usize) -> String {
+    exact_units(s, 1024, IEC_NAMES)
+}
+
+/// `lossless_bytes` produces the shortest representation of a size, in either
+/// SI or IEC units, which `parse_bytes` parses back to exactly the same size.
+///
+/// lossless_bytes(44564480) -> 42.5MiB
+/// lossless_bytes(1500) -> 1.5kB
+#[must_use]
+pub fn lossless_bytes(s: usize) -> String {
+    // Ties go to the larger unit, then to IEC
+    let mut shortest = (exact_ibytes(s), 1);
+
+    for (base, names) in [(1024, IEC_NAMES), (1000, SI_NAMES)] {
+        let mut scale = 1u128;
+        for name in names {
+            if scale > s as u128 {
+                break;
+            }
+
+            // Find the fewest decimals which represent the size exactly
+            let (whole, mut rem) = (s as u128 / scale, s as u128 % scale);
+            let mut frac = String::new();
+            while rem != 0 && frac.len() < shortest.0.len() {
+                rem *= 10;
+                frac.push((b'0' + (rem / scale) as u8) as char);
+                rem %= scale;
+            }
+
+            let candidate = if frac.is_empty() {
+                format!("{}{}", whole, name)
+            } else {
+                format!("{}.{}{}", whole, frac, name)
+            };
+            let shorter = (candidate.len(), std::cmp::Reverse(scale))
+                < (shortest.0.len(), std::cmp::Reverse(shortest.1));
+            if rem == 0 && shorter && parse_bytes(&candidate).is_ok_and(|size| size == s) {
+                shortest = (candidate, scale);
+            }
+
+            scale *= base;
+        }
+    }
+
+    shortest.0
+}
+
+/// `Sign` controls which signed sizes are formatted with a sign.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Sign {
+    /// Only negative sizes have a sign, e.g. "-300MB".
+    #[default]
+    Negative,
+    /// Positive sizes have a '+' sign too, e.g. "+1.2GiB", zero has none.
+    Always,
 }
 
-/// `parse_bytes` parses a string representation of bytes into the number of bytes it represents
+/// `signed_bytes` is `bytes` for size deltas.
+///
+/// See also: `parse_signed_bytes`
+///
+/// signed_bytes(-300000000, Sign::Negative) -> -300MB
+/// signed_bytes(1200000000, Sign::Always) -> +1.2GB
+#[must_use]
+pub fn signed_bytes(s: i64, sign: Sign) -> String {
+    signed(s.into(), sign, 1000.0, SI_NAMES)
+}
+
+/// `signed_ibytes` is `ibytes` for size deltas.
+///
+/// signed_ibytes(1288490189, Sign::Always) -> +1.2GiB
+#[must_use]
+pub fn signed_ibytes(s: i64, sign: Sign) -> String {
+    signed(s.into(), sign, 1024.0, IEC_NAMES)
+}
+
+/// `signed_bytes_i128` is `signed_bytes` for deltas beyond `i64`, e.g. the
+/// difference of two `usize` sizes. A thousand exabytes or more are printed
+/// as exact bytes.
+///
+/// signed_bytes_i128(-(u64::MAX as i128), Sign::Negative) -> -18.4EB
+/// signed_bytes_i128(i128::MAX, Sign::Always) -> +170141183460469231731687303715884105727B
+#[must_use]
+pub fn signed_bytes_i128(s: i128, sign: Sign) -> String {
+    signed(s, sign, 1000.0, SI_NAMES)
+}
+
+/// `signed_ibytes_i128` is `signed_ibytes` for deltas beyond `i64`.
+#[must_use]
+pub fn signed_ibytes_i128(s: i128, sign: Sign) -> String {
+    signed(s, sign, 1024.0, IEC_NAMES)
+}
+
+fn signed(s: i128, sign: Sign, base: f64, names: [&str; 7]) -> String {
+    let prefix = match (s.signum(), sign) {
+        (-1, _) => "-",
+        (1, Sign::Always) => "+",
+        _ => "",
+    };
+
+    format!(
+        "{}{}",
+        prefix,
+        humanate_bytes(s.unsigned_abs(), base, names)
+    )
+}
+
+const SI_NAMES: [&str; 7] = ["B", "kB", "MB", "GB", "TB", "PB", "EB"];
+const IEC_NAMES: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+fn exact_units(mut s: usize, base: usize, names: [&str; 7]) -> String {
+    let mut e = 0;
+    while s != 0 && s.is_multiple_of(base) && e < names.len() - 1 {
+        s /= base;
+        e += 1;
+    }
+
+    format!("{}{}", s, names[e])
+}
+
+/// `parse_bytes` parses a string representation of bytes into the number of bytes it represents.
+/// Numbers may have a decimal exponent, or be a power of an integer.
 ///
 /// parse_bytes("42 MB") -> Ok(42000000)
 /// parse_bytes("42 mib") -> Ok(44040192)
+/// parse_bytes("1.5E3 KB") -> Ok(1500000)
+/// parse_bytes("2^30") -> Ok(1073741824)
 ///
 /// # Errors
 ///
-/// Return `Error` if the input is not valid.
+/// Return `Error` if the input is not valid, or `Error::TooLarge` if the size
+/// overflows.
 pub fn parse_bytes(input: &str) -> Result<usize, Error<'_>> {
-    let mut last_digit = 0;
+    let (number, unit) = input.split_at(number_len(input.as_bytes()));
+    let (mantissa, exp) = split_exponent(number.as_bytes());
+    number[..mantissa.len()].parse::<f64>()?;
+    let unit = unit.trim();
+    let scale = unit_scale(unit).ok_or(Error::UnknownUnit { unit })?;
+
+    scaled_size(mantissa, exp, scale).ok_or(Error::TooLarge { input })
+}
+
+/// `parse_bytes_const` is `parse_bytes` for const contexts, it returns None if
+/// the input is not valid. Only ASCII whitespace is allowed around the unit.
+///
+/// See also: `bytes!`
+///
+/// const DEFAULT_BUF: usize = parse_bytes_const("64KiB").unwrap(); -> 65536
+#[must_use]
+pub const fn parse_bytes_const(input: &str) -> Option<usize> {
+    let (number, unit) = split_number(input);
+    match unit_scale(unit.trim_ascii()) {
+        Some(scale) => number_size(number, scale),
+        None => None,
+    }
+}
+
+/// bytes! parses a size at compile time, invalid sizes fail compilation.
+///
+/// const DEFAULT_BUF: usize = bytes!("64KiB"); -> 65536
+#[macro_export]
+macro_rules! bytes {
+    ($input:expr) => {{
+        const SIZE: usize = match $crate::bytes::parse_bytes_const($input) {
+            Some(size) => size,
+            None => panic!(concat!("invalid size ", stringify!($input))),
+        };
+        SIZE
+    }};
+}
+
+// Exponents are capped, any larger exponent overflows, or rounds to 0
+const MAX_EXPONENT: i64 = 1000;
+
+#[derive(Clone, Copy)]
+enum Exponent {
+    /// The mantissa times 10^N, e.g. "1.5e3"
+    Ten(i64),
+    /// The mantissa to the power of N, e.g. "2^30"
+    Power(i64),
+}
+
+/// number_len returns the length of the leading number of s, a [0-9.]*
+/// mantissa with an optional "e[+-]N" exponent, or "^N" for integers.
+const fn number_len(s: &[u8]) -> usize {
+    let mut i = 0;
+    let mut integer = true;
+    while i < s.len() && (s[i].is_ascii_digit() || s[i] == b'.') {
+        integer = integer && s[i] != b'.';
+        i += 1;
+    }
+
+    let mut j = i + 1;
+    match s.split_at(i).1 {
+        [b'e' | b'E', b'+' | b'-', ..] => j += 1,
+        [b'e' | b'E', ..] => {}
+        [b'^', ..] if integer && i > 0 => {}
+        _ => return i,
+    }
+
+    let digits = j;
+    while j < s.len() && s[j].is_ascii_digit() {
+        j += 1;
+    }
+
+    // Without digits, "e" is a unit, e.g. "1e" is an exabyte
+    if j > digits { j } else { i }
+}
+
+/// split_exponent splits a number into its mantissa and exponent.
+const fn split_exponent(number: &[u8]) -> (&[u8], Exponent) {
+    let mut i = 0;
+    while i < number.len() && (number[i].is_ascii_digit() || number[i] == b'.') {
+        i += 1;
+    }
+
+    let (mantissa, rest) = number.split_at(i);
+    let (power, neg, mut j) = match rest {
+        [] => return (mantissa, Exponent::Ten(0)),
+        [b'^', ..] => (true, false, 1),
+        [_, b'-', ..] => (false, true, 2),
+        [_, b'+', ..] => (false, false, 2),
+        _ => (false, false, 1),
+    };
+
+    let mut exp = 0;
+    while j < rest.len() {
+        exp = exp * 10 + (rest[j] - b'0') as i64;
+        if exp > MAX_EXPONENT {
+            exp = MAX_EXPONENT;
+        }
+        j += 1;
+    }
+
+    match (power, neg) {
+        (true, _) => (mantissa, Exponent::Power(exp)),
+        (false, true) => (mantissa, Exponent::Ten(-exp)),
+        (false, false) => (mantissa, Exponent::Ten(exp)),
+    }
+}
+
+/// valid_number reports whether a [0-9.]* number parses as a float, i.e. it
+/// has a digit and at most one dot.
+const fn valid_number(s: &[u8]) -> bool {
+    let (mut digits, mut dots, mut i) = (0, 0, 0);
+    while i < s.len() {
+        if s[i] == b'.' {
+            dots += 1;
+        } else {
+            digits += 1;
+        }
+        i += 1;
+    }
+
+    digits > 0 && dots <= 1
+}
+
+/// split_number splits off the leading number of s, as `parse_bytes` reads it.
+pub(crate) const fn split_number(s: &str) -> (&str, &str) {
+    s.split_at(number_len(s.as_bytes()))
+}
+
+/// number_size multiplies a number from `split_number` by scale. It returns
+/// None if the number is not valid or on overflow.
+pub(crate) const fn number_size(number: &str, scale: usize) -> Option<usize> {
+    let (mantissa, exp) = split_exponent(number.as_bytes());
+    if !valid_number(mantissa) {
+        return None;
+    }
+
+    scaled_size(mantissa, exp, scale)
+}
+
+/// scaled_size multiplies a valid mantissa with its exponent by scale. It
+/// returns None on overflow.
+const fn scaled_size(mantissa: &[u8], exp: Exponent, scale: usize) -> Option<usize> {
+    match exp {
+        Exponent::Ten(shift) => exact_size(mantissa, shift, scale),
+        Exponent::Power(exp) => {
+            let Some(base) = exact_size(mantissa, 0, 1) else {
+                return None;
+            };
+
+            // 0^0 and 1^N are 1, larger bases overflow in at most 64 steps
+            let mut size = scale;
+            let mut i = 0;
+            while i < exp && size != 0 && base != 1 {
+                size = match size.checked_mul(base) {
+                    Some(size) => size,
+                    None => return None,
+                };
+                i += 1;
+            }
+
+            Some(size)
+        }
+    }
+}
+
+/// digit_at returns digit i of a number, not counting its dot at index `dot`,
+/// digits outside of the number are 0.
+const fn digit_at(number: &[u8], dot: usize, i: i64) -> u8 {
+    let len = if dot < number.len() {
+        number.len() - 1
+    } else {
+        number.len()
+    } as i64;
+    if i < 0 || i >= len {
+        0
+    } else if i < dot as i64 {
+        number[i as usize] - b'0'
+    } else {
+        number[i as usize + 1] - b'0'
+    }
+}
+
+/// exact_size multiplies a valid [0-9.]* number, with its decimal point moved
+/// `shift` places to the right, by scale without going through floats. It
+/// returns None on overflow.
+const fn exact_size(number: &[u8], shift: i64, scale: usize) -> Option<usize> {
+    let mut dot = number.len();
+    let mut zero = true;
+    let mut i = 0;
+    while i < number.len() {
+        if number[i] == b'.' {
+            dot = i;
+        } else if number[i] != b'0' {
+            zero = false;
+        }
+        i += 1;
+    }
+    if zero {
+        return Some(0);
+    }
+
+    // Digit positions, skipping the dot, the shifted point is before `point`
+    let len = if dot < number.len() {
+        number.len() - 1
+    } else {
+        number.len()
+    } as i64;
+    let point = dot as i64 + shift;
+
+    let mut int = 0usize;
+    let mut i = 0;
+    while i < point {
+        int = match int.checked_mul(10) {
+            Some(v) => match v.checked_add(digit_at(number, dot, i) as usize) {
+                Some(v) => v,
+                None => return None,
+            },
+            None => return None,
+        };
+        i += 1;
+    }
+
+    // Digits beyond the 19th add up to less than a byte
+    let (mut digits, mut div) = (0u128, 1u128);
+    let mut i = point;
+    while i < len && div < 10u128.pow(19) {
+        digits = digits * 10 + digit_at(number, dot, i) as u128;
+        div *= 10;
+        i += 1;
+    }
+    let frac = (digits * scale as u128 / div) as usize;
+
+    match int.checked_mul(scale) {
+        Some(size) => size.checked_add(frac),
+        None => None,
+    }
+}
+
+const SI_SHORT_UNITS: [(&str, usize); 7] = [
+    ("b", IBYTE),
+    ("k", KBYTE),
+    ("m", MBYTE),
+    ("g", GBYTE),
+    ("t", TBYTE),
+    ("p", PBYTE),
+    ("e", EBYTE),
+];
+const SI_UNITS: [(&str, usize); 6] = [
+    ("kb", KBYTE),
+    ("mb", MBYTE),
+    ("gb", GBYTE),
+    ("tb", TBYTE),
+    ("pb", PBYTE),
+    ("eb", EBYTE),
+];
+const IEC_SHORT_UNITS: [(&str, usize); 6] = [
+    ("ki", KIBYTE),
+    ("mi", MIBYTE),
+    ("gi", GIBYTE),
+    ("ti", TIBYTE),
+    ("pi", PIBYTE),
+    ("ei", EIBYTE),
+];
+const IEC_UNITS: [(&str, usize); 6] = [
+    ("kib", KIBYTE),
+    ("mib", MIBYTE),
+    ("gib", GIBYTE),
+    ("tib", TIBYTE),
+    ("pib", PIBYTE),
+    ("eib", EIBYTE),
+];
+
+/// unit_scale returns the size of a unit, the empty unit is a byte.
+pub(crate) const fn unit_scale(unit: &str) -> Option<usize> {
+    if unit.is_empty() {
+        return Some(BYTE);
+    }
+
+    let tables: [&[(&str, usize)]; 4] = [&SI_SHORT_UNITS, &SI_UNITS, &IEC_SHORT_UNITS, &IEC_UNITS];
+    let mut t = 0;
+    while t < tables.len() {
+        let mut i = 0;
+        while i < tables[t].len() {
+            let (name, scale) = tables[t][i];
+            if unit.eq_ignore_ascii_case(name) {
+                return Some(scale);
+            }
+            i += 1;
+        }
+        t += 1;
+    }
+
+    None
+}
+
+/// `SizeSpec` is a size which is either an absolute number of bytes, or a
+/// percentage of some total, e.g. the available memory or disk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SizeSpec {
+    Absolute(usize),
+    /// Fraction of the total, `0.1` means 10%.
+    Relative(f64),
+}
+
+impl SizeSpec {
+    /// resolve returns the number of bytes this spec represents for the given total.
+    #[must_use]
+    pub fn resolve(&self, total: usize) -> usize {
+        match self {
+            SizeSpec::Absolute(size) => *size,
+            SizeSpec::Relative(ratio) => (total as f64 * ratio) as usize,
+        }
+    }
+}
+
+impl Display for SizeSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SizeSpec::Absolute(size) => write!(f, "{}", size),
+            SizeSpec::Relative(ratio) => write!(f, "{}%", percentage(*ratio)),
+        }
+    }
+}
+
+/// percentage returns the shortest percentage which `parse_size_spec` reads
+/// back as `ratio`, `ratio * 100.0` would format 0.07 as "7.000000000000001".
+fn percentage(ratio: f64) -> String {
+    let pct = ratio * 100.0;
+    (0..=17)
+        .map(|precision| format!("{:.*}", precision, pct))
+        .find(|s| s.parse::<f64>().is_ok_and(|pct| pct / 100.0 == ratio))
+        .unwrap_or_else(|| pct.to_string())
+}
+
+/// `parse_size_spec` parses either a size accepted by `parse_bytes`, or a percentage.
+/// The percentage can be followed by a description of what it is relative to,
+/// which is informational only, the total is always given to `SizeSpec::resolve`.
+///
+/// parse_size_spec("512MiB") -> Ok(SizeSpec::Absolute(536870912))
+/// parse_size_spec("10%") -> Ok(SizeSpec::Relative(0.1))
+/// parse_size_spec("25% of memory") -> Ok(SizeSpec::Relative(0.25))
+///
+/// # Errors
+///
+/// Return `Error::InvalidPercent` if the percentage is not a plain decimal,
+/// or `Error` if the input is not valid.
+pub fn parse_size_spec(input: &str) -> Result<SizeSpec, Error<'_>> {
+    let input = input.trim();
+
+    let Some(pos) = input.find('%') else {
+        return parse_bytes(input).map(SizeSpec::Absolute);
+    };
+
+    // Allow a trailing description, e.g. "25% of memory"
+    let remain = input[pos + 1..].trim();
+    if !remain.is_empty() {
+        match remain.split_once(char::is_whitespace) {
+            Some((of, target)) if of.eq_ignore_ascii_case("of") && !target.trim().is_empty() => {}
+            _ => return Err(Error::UnknownUnit { unit: remain }),
+        }
+    }
+
+    // Only plain decimals, parse::<f64> would accept "-5", "inf", "NaN" and "1e2"
+    let number = input[..pos].trim_end();
+    let plain = number.bytes().all(|c| c.is_ascii_digit() || c == b'.');
+    match number.parse::<f64>() {
+        Ok(flt) if plain && flt.is_finite() => Ok(SizeSpec::Relative(flt / 100.0)),
+        _ => Err(Error::InvalidPercent {
+            input: &input[..=pos],
+        }),
+    }
+}
+
+/// `parse_rate` parses a transfer rate, a size accepted by `parse_bytes` per
+/// second, minute, hour or day, into the number of bytes per second. The rate
+/// is rounded down to whole bytes, and must be at least 1B/s.
+///
+/// parse_rate("10MiB/s") -> Ok(10485760)
+/// parse_rate("1.5 GB/min") -> Ok(25000000)
+///
+/// # Errors
+///
+/// Return `Error::InvalidRate` if the input is not "SIZE/PERIOD" or is below
+/// 1B/s, or `Error` if the size or period is not valid.
+pub fn parse_rate(input: &str) -> Result<usize, Error<'_>> {
+    let Some((size, period)) = input.rsplit_once('/') else {
+        return Err(Error::InvalidRate { input });
+    };
+
+    let seconds = match period.trim() {
+        "s" | "sec" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        unit => return Err(Error::UnknownUnit { unit }),
+    };
+
+    match parse_bytes(size.trim())? / seconds {
+        0 => Err(Error::InvalidRate { input }),
+        rate => Ok(rate),
+    }
+}
+
+/// `parse_signed_bytes` parses a size delta, a size accepted by `parse_bytes`
+/// with an optional sign, like `duration::parse_duration` accepts `[-+]?`.
+///
+/// parse_signed_bytes("-512MiB") -> Ok(-536870912)
+/// parse_signed_bytes("+1GB") -> Ok(1000000000)
+///
+/// # Errors
+///
+/// Return `Error` if the input is not valid, or `Error::TooLarge` if the size
+/// does not fit in an `i64`.
+pub fn parse_signed_bytes(input: &str) -> Result<i64, Error<'_>> {
+    i64::try_from(parse_signed_bytes_i128(input)?).map_err(|_| Error::TooLarge { input })
+}
+
+/// `parse_signed_bytes_i128` is `parse_signed_bytes` for any size accepted by
+/// `parse_bytes`, with either sign.
+///
+/// parse_signed_bytes_i128("-16EiB") -> Err(too large "16EiB")
+/// parse_signed_bytes_i128("-15EiB") -> Ok(-17293822569102704640)
+///
+/// # Errors
+///
+/// Return `Error` if the input is not valid.
+pub fn parse_signed_bytes_i128(input: &str) -> Result<i128, Error<'_>> {
+    let (neg, size) = match input.as_bytes().first() {
+        Some(b'-') => (true, &input[1..]),
+        Some(b'+') => (false, &input[1..]),
+        _ => (false, input),
+    };
+
+    let size = parse_bytes(size)? as i128;
+    Ok(if neg { -size } else { size })
+}
+
+/// `SizeRange` is an interval of sizes, e.g. files between 1MiB and 1GiB.
+///
+/// See also: `parse_size_range`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SizeRange {
+    start: Bound<usize>,
+    end: Bound<usize>,
+}
+
+impl SizeRange {
+    #[must_use]
+    pub const fn new(start: Bound<usize>, end: Bound<usize>) -> Self {
+        Self { start, end }
+    }
+
+    #[must_use]
+    pub const fn start(&self) -> Bound<usize> {
+        self.start
+    }
+
+    #[must_use]
+    pub const fn end(&self) -> Bound<usize> {
+        self.end
+    }
+
+    /// contains reports whether the size is in the range.
+    #[must_use]
+    pub fn contains(&self, size: usize) -> bool {
+        RangeBounds::contains(self, &size)
+    }
+
+    /// is_empty reports whether the range contains no sizes, e.g. "1KiB..1KiB".
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        let start = match self.start {
+            Bound::Included(start) => start,
+            Bound::Excluded(start) => match start.checked_add(1) {
+                Some(start) => start,
+                None => return true,
+            },
+            Bound::Unbounded => 0,
+        };
 
-    for ch in input.chars() {
-        if !(ch.is_ascii_digit() || ch == '.') {
-            break;
+        match self.end {
+            Bound::Included(end) => start > end,
+            Bound::Excluded(end) => start >= end,
+            Bound::Unbounded => false,
         }
+    }
+}
 
-        last_digit += 1;
+impl RangeBounds<usize> for SizeRange {
+    fn start_bound(&self) -> Bound<&usize> {
+        self.start.as_ref()
+    }
+
+    fn end_bound(&self) -> Bound<&usize> {
+        self.end.as_ref()
+    }
+}
+
+/// Formats the range in the syntax of `parse_size_range`, with sizes from
+/// `lossless_bytes`. Empty ranges are all formatted as "0B..0B".
+impl Display for SizeRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        use Bound::{Excluded, Included, Unbounded};
+
+        if self.is_empty() {
+            return write!(f, "0B..0B");
+        }
+
+        let start = match self.start {
+            // Sizes are integers, so "1KiB<" starts at 1025B, which can't
+            // overflow as the range is not empty
+            Excluded(start) if self.end != Unbounded => Included(start + 1),
+            start => start,
+        };
+
+        let b = lossless_bytes;
+        match (start, self.end) {
+            (Unbounded, Unbounded) => write!(f, ".."),
+            (Unbounded, Excluded(end)) => write!(f, "<{}", b(end)),
+            (Unbounded, Included(end)) => write!(f, "<={}", b(end)),
+            (Excluded(start), _) => write!(f, ">{}", b(start)),
+            (Included(start), Unbounded) => write!(f, ">={}", b(start)),
+            (Included(start), Included(end)) if start == end => write!(f, "{}", b(start)),
+            (Included(start), Included(end)) => write!(f, "{}..={}", b(start), b(end)),
+            (Included(start), Excluded(end)) => write!(f, "{}..{}", b(start), b(end)),
+        }
     }
+}
 
-    let flt = &input[..last_digit].parse::<f64>()?;
-    let unit = input[last_digit..].trim();
+/// `parse_size_range` parses an interval of sizes accepted by `parse_bytes`.
+/// "A..B" excludes B like Rust ranges, "A..=B" and "A-B" include it, either
+/// side of ".." may be left out. ">A", ">=A", "<A" and "<=A" are open ranges,
+/// and a single size is a range of just that size.
+///
+/// parse_size_range("1KiB-4MiB") -> Ok(1KiB..=4MiB)
+/// parse_size_range(">=10GB") -> Ok(10GB..)
+///
+/// # Errors
+///
+/// Return `Error` if a size is not valid, or `Error::InvalidRange` if the
+/// range is malformed or its start is after its end.
+pub fn parse_size_range(input: &str) -> Result<SizeRange, Error<'_>> {
+    use Bound::{Excluded, Included, Unbounded};
 
-    let scale = match unit.len() {
-        0 => BYTE,
-        1 => calculate_scale(unit, 1000, &["b", "k", "m", "g", "t", "p", "e"])
-            .ok_or(Error::UnknownUnit { unit })?,
-        2 => calculate_scale(unit, 1000, &["", "kb", "mb", "gb", "tb", "pb", "eb"])
-            .or_else(|| calculate_scale(unit, 1024, &["", "ki", "mi", "gi", "ti", "pi", "ei"]))
-            .ok_or(Error::UnknownUnit { unit })?,
-        3 => calculate_scale(unit, 1024, &["", "kib", "mib", "gib", "tib", "pib", "eib"])
-            .ok_or(Error::UnknownUnit { unit })?,
-        _ => return Err(Error::UnknownUnit { unit }),
+    let s = input.trim();
+    fn size(s: &str) -> Result<usize, Error<'_>> {
+        parse_bytes(s.trim())
+    }
+    fn bound(s: &str, bound: fn(usize) -> Bound<usize>) -> Result<Bound<usize>, Error<'_>> {
+        match s.trim() {
+            "" => Ok(Bound::Unbounded),
+            s => parse_bytes(s).map(bound),
+        }
+    }
+
+    let range = if let Some(end) = s.strip_prefix("<=") {
+        SizeRange::new(Unbounded, Included(size(end)?))
+    } else if let Some(end) = s.strip_prefix('<') {
+        SizeRange::new(Unbounded, Excluded(size(end)?))
+    } else if let Some(start) = s.strip_prefix(">=") {
+        SizeRange::new(Included(size(start)?), Unbounded)
+    } else if let Some(start) = s.strip_prefix('>') {
+        SizeRange::new(Excluded(size(start)?), Unbounded)
+    } else if let Some((start, end)) = s.split_once("..=") {
+        if end.trim().is_empty() {
+            return Err(Error::InvalidRange { input });
+        }
+        SizeRange::new(bound(start, Included)?, bound(end, Included)?)
+    } else if let Some((start, end)) = s.split_once("..") {
+        SizeRange::new(bound(start, Included)?, bound(end, Excluded)?)
+    } else if s.contains('-') {
+        // Exponents may have a '-' too, e.g. "1e-3MB-1KB"
+        let mut dashes = s.match_indices('-').map(|(i, _)| (&s[..i], &s[i + 1..]));
+        let split = dashes.find(|(start, end)| size(start).is_ok() && size(end).is_ok());
+        let Some((start, end)) = split else {
+            return Err(Error::InvalidRange { input });
+        };
+        SizeRange::new(Included(size(start)?), Included(size(end)?))
+    } else {
+        let size = size(s)?;
+        SizeRange::new(Included(size), Included(size))
     };
 
-    Ok((flt * scale as f64) as usize)
+    match (range.start, range.end) {
+        (Included(start) | Excluded(start), Included(end) | Excluded(end)) if start > end => {
+            Err(Error::InvalidRange { input })
+        }
+        _ => Ok(range),
+    }
+}
+
+/// `from_env` parses the environment variable `key` with `parse_bytes`.
+///
+/// from_env("APP_CACHE_SIZE") -> Ok(536870912) with APP_CACHE_SIZE=512MiB
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is unset or not valid.
+#[cfg(feature = "env")]
+pub fn from_env(key: &str) -> Result<usize, crate::env::Error> {
+    from_source(&crate::env::Env, key)
+}
+
+/// `from_env_or` is like `from_env`, but returns `default` if the variable is
+/// unset or empty.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is not valid.
+#[cfg(feature = "env")]
+pub fn from_env_or(key: &str, default: usize) -> Result<usize, crate::env::Error> {
+    from_source_or(&crate::env::Env, key, default)
+}
+
+/// `from_source` is `from_env` reading from any `env::Source`.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is unset or not valid.
+#[cfg(feature = "env")]
+pub fn from_source<S>(source: &S, key: &str) -> Result<usize, crate::env::Error>
+where
+    S: crate::env::Source + ?Sized,
+{
+    crate::env::require(source, key, |v| parse_bytes(v).map_err(|e| e.to_string()))
+}
+
+/// `from_source_or` is `from_env_or` reading from any `env::Source`.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is not valid.
+#[cfg(feature = "env")]
+pub fn from_source_or<S>(source: &S, key: &str, default: usize) -> Result<usize, crate::env::Error>
+where
+    S: crate::env::Source + ?Sized,
+{
+    let size = crate::env::parse(source, key, |v| parse_bytes(v).map_err(|e| e.to_string()))?;
+    Ok(size.unwrap_or(default))
+}
+
+/// `Bounds` restricts sizes to an inclusive range, and optionally to multiples
+/// of a size, e.g. page-aligned buffers.
+///
+/// Bounds::new().min(4 * 1024).max(1 << 30).parse("2GiB") -> Err(must be at most 1GiB, got 2GiB)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bounds {
+    min: usize,
+    max: usize,
+    multiple_of: usize,
 }
 
-fn calculate_scale(input: &str, base: usize, units: &[&str]) -> Option<usize> {
-    units.iter().enumerate().find_map(|(index, unit)| {
-        if input.eq_ignore_ascii_case(unit) {
-            Some(base.pow(index as u32))
+impl Default for Bounds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bounds {
+    /// new returns bounds which accept any size.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            min: 0,
+            max: usize::MAX,
+            multiple_of: 1,
+        }
+    }
+
+    /// min sets the smallest accepted size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is greater than the largest accepted size.
+    #[must_use]
+    pub const fn min(mut self, min: usize) -> Self {
+        assert!(min <= self.max, "bounds min is greater than max");
+        self.min = min;
+        self
+    }
+
+    /// max sets the largest accepted size.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is less than the smallest accepted size.
+    #[must_use]
+    pub const fn max(mut self, max: usize) -> Self {
+        assert!(self.min <= max, "bounds min is greater than max");
+        self.max = max;
+        self
+    }
+
+    /// multiple_of only accepts multiples of `size`, 0 and 1 accept any size.
+    #[must_use]
+    pub const fn multiple_of(mut self, size: usize) -> Self {
+        self.multiple_of = if size == 0 { 1 } else { size };
+        self
+    }
+
+    /// check returns `size` if it is within the bounds.
+    ///
+    /// # Errors
+    ///
+    /// Return `OutOfBounds` if the size is not within the bounds.
+    pub fn check(&self, size: usize) -> Result<usize, OutOfBounds> {
+        if size < self.min {
+            Err(OutOfBounds::TooSmall {
+                min: self.min,
+                got: size,
+            })
+        } else if size > self.max {
+            Err(OutOfBounds::TooLarge {
+                max: self.max,
+                got: size,
+            })
+        } else if !size.is_multiple_of(self.multiple_of) {
+            Err(OutOfBounds::NotMultiple {
+                multiple_of: self.multiple_of,
+                got: size,
+            })
         } else {
-            None
+            Ok(size)
+        }
+    }
+
+    /// parse parses a size with `parse_bytes` and checks it is within the bounds.
+    ///
+    /// # Errors
+    ///
+    /// Return `Error` if the input is not valid, or `Error::OutOfBounds` if the
+    /// size is not within the bounds.
+    pub fn parse<'a>(&self, input: &'a str) -> Result<usize, Error<'a>> {
+        Ok(self.check(parse_bytes(input)?)?)
+    }
+}
+
+/// `OutOfBounds` is a size rejected by `Bounds`, it formats the sizes with
+/// `lossless_bytes`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum OutOfBounds {
+    TooSmall { min: usize, got: usize },
+    TooLarge { max: usize, got: usize },
+    NotMultiple { multiple_of: usize, got: usize },
+}
+
+impl std::error::Error for OutOfBounds {}
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            OutOfBounds::TooSmall { min, got } => write!(
+                f,
+                "must be at least {}, got {}",
+                lossless_bytes(min),
+                lossless_bytes(got)
+            ),
+            OutOfBounds::TooLarge { max, got } => write!(
+                f,
+                "must be at most {}, got {}",
+                lossless_bytes(max),
+                lossless_bytes(got)
+            ),
+            OutOfBounds::NotMultiple { multiple_of, got } => write!(
+                f,
+                "must be a multiple of {}, got {}",
+                lossless_bytes(multiple_of),
+                lossless_bytes(got)
+            ),
         }
-    })
+    }
+}
+
+/// `compare_human` compares two human-readable sizes like `sort -h`, e.g. the
+/// output of `du -h`. Numbers are ordered by sign, then by suffix, then by
+/// value, so "2K" < "1M" < "1.5G". Input which is not a number compares as
+/// zero, ties are broken by comparing the strings byte by byte.
+///
+/// compare_human("2K", "1M") -> Ordering::Less
+/// compare_human("1.5GiB", "1G") -> Ordering::Greater
+#[must_use]
+pub fn compare_human(a: &str, b: &str) -> Ordering {
+    human_key(a).cmp(&human_key(b))
+}
+
+/// `human_key` returns the sort key used by `compare_human`, to be used with
+/// `sort_by_key` and friends.
+///
+/// sizes.sort_by_cached_key(|s| human_key(s))
+#[must_use]
+pub fn human_key(input: &str) -> HumanKey {
+    let trimmed = input.trim_start_matches([' ', '\t']);
+    let (neg, number) = match trimmed.strip_prefix('-') {
+        Some(number) => (true, number),
+        None => (false, trimmed),
+    };
+
+    let int_len = number.bytes().take_while(u8::is_ascii_digit).count();
+    let (int, remain) = number.split_at(int_len);
+    let (frac, remain) = match remain.strip_prefix('.') {
+        Some(remain) => {
+            let frac_len = remain.bytes().take_while(u8::is_ascii_digit).count();
+            remain.split_at(frac_len)
+        }
+        None => ("", remain),
+    };
+
+    let int = int.trim_start_matches('0');
+    let frac = frac.trim_end_matches('0');
+    let is_zero = int.is_empty() && frac.is_empty();
+
+    // Only the suffix right after a non-zero number counts, as `sort -h` does
+    let order = match remain.bytes().next() {
+        Some(suffix) if !is_zero => HUMAN_SUFFIXES
+            .iter()
+            .position(|&c| c == suffix.to_ascii_uppercase() && (c == b'K' || suffix == c))
+            .map_or(0, |i| i as i32 + 1),
+        _ => 0,
+    };
+
+    HumanKey {
+        order: if neg { -order } else { order },
+        neg: neg && !is_zero,
+        int: int.to_string(),
+        frac: frac.to_string(),
+        input: input.to_string(),
+    }
+}
+
+const HUMAN_SUFFIXES: [u8; 10] = *b"KMGTPEZYRQ";
+
+/// `HumanKey` is the sort key of a human-readable size, see `human_key`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HumanKey {
+    order: i32,
+    neg: bool,
+    int: String,
+    frac: String,
+    input: String,
+}
+
+impl HumanKey {
+    /// Compares the magnitude of the numbers, ignoring the sign and suffix.
+    fn cmp_number(&self, other: &Self) -> Ordering {
+        self.int
+            .len()
+            .cmp(&other.int.len())
+            .then_with(|| self.int.cmp(&other.int))
+            .then_with(|| self.frac.cmp(&other.frac))
+    }
+}
+
+impl Ord for HumanKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let number = match (self.neg, other.neg) {
+            (false, false) => self.cmp_number(other),
+            (true, true) => other.cmp_number(self),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        };
+
+        self.order
+            .cmp(&other.order)
+            .then(number)
+            .then_with(|| self.input.cmp(&other.input))
+    }
+}
+
+impl PartialOrd for HumanKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
 #[inline]
@@ -116,66 +1126,615 @@ fn logn(n: f64, b: f64) -> f64 {
     n.log2() / b.log2()
 }
 
-fn humanate_bytes(s: usize, base: f64, sizes: [&str; 7]) -> String {
+fn humanate_bytes(s: u128, base: f64, sizes: [&str; 7]) -> String {
+    // A thousand or more of the largest unit has more digits than f64 holds
+    if s >= (base as u128).pow(sizes.len() as u32) {
+        return format!("{}{}", s, sizes[0]);
+    }
+
+    let (val, e) = humanate(s, base);
+
+    format!("{}{}", val, sizes[e])
+}
+
+/// humanate returns a size in its largest unit, rounded to one decimal, and
+/// the exponent of that unit, at most 6 for exabytes.
+pub(crate) fn humanate(s: u128, base: f64) -> (f64, usize) {
     if s < 10 {
-        return format!("{}B", s);
+        return (s as f64, 0);
     }
 
-    let e = logn(s as f64, base).floor();
-    let suffix = sizes[e as usize];
+    let e = logn(s as f64, base).floor().min(6.0);
     let val = s as f64 / base.powf(e) * 10.0 + 0.5;
-    let val = val.floor() / 10.0;
 
-    format!("{}{}", val, suffix)
+    (val.floor() / 10.0, e as usize)
 }
 
 #[cfg(feature = "serde")]
 pub mod serde {
-    use std::borrow::Cow;
+    use std::fmt::Formatter;
+
+    use serde_core::de::{Error, Unexpected, Visitor};
+    use serde_core::{Deserializer, Serializer};
+
+    use super::{ibytes, parse_bytes};
+
+    /// Accepts a number of bytes, or a string parsed with `parse_bytes`.
+    pub(super) struct BytesVisitor;
+
+    impl<'de> Visitor<'de> for BytesVisitor {
+        type Value = usize;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(f, "a number of bytes or a size string like \"42MiB\"")
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            usize::try_from(v).map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            usize::try_from(v).map_err(|_| E::invalid_value(Unexpected::Signed(v), &self))
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            if v.is_finite() && v >= 0.0 && v.fract() == 0.0 && v < usize::MAX as f64 {
+                Ok(v as usize)
+            } else {
+                Err(E::invalid_value(Unexpected::Float(v), &self))
+            }
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse_bytes(v).map_err(Error::custom)
+        }
+    }
+
+    /// deserialize reads a number or a string from self-describing formats like
+    /// JSON, but only the string written by `serialize` from formats like
+    /// bincode, which can't tell which one comes next.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BytesVisitor)
+        } else {
+            deserializer.deserialize_str(BytesVisitor)
+        }
+    }
+
+    pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
+        let b = ibytes(*u);
+        s.serialize_str(&b)
+    }
+
+    /// Matches the strings accepted by `parse_bytes`.
+    #[cfg(feature = "schemars")]
+    pub(super) const PATTERN: &str = r"^(([0-9]+(\.[0-9]*)?|\.[0-9]+)([eE][-+]?[0-9]+)?|[0-9]+\^[0-9]+)\s*([bB]|[kKmMgGtTpPeE][iI]?[bB]?)?\s*$";
+
+    /// `json_schema` describes the sizes accepted by `deserialize`, to be used with
+    /// `#[schemars(schema_with = "humanize::bytes::serde::json_schema")]`.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schema(&["string", "number"])
+    }
+
+    #[cfg(feature = "schemars")]
+    pub(super) fn schema(types: &[&str]) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A size like \"512MiB\" or \"1.5GB\", or a number of bytes",
+            "type": types,
+            "pattern": PATTERN,
+            "minimum": 0,
+            "examples": ["512MiB", "1.5GB", 1048576],
+        })
+    }
+
+    /// Serializes sizes as a number of bytes.
+    pub mod raw {
+        use serde_core::{Deserializer, Serializer};
+
+        use super::BytesVisitor;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+
+        /// Like `serde::deserialize`, but formats like bincode read the number
+        /// written by `serialize`.
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_any(BytesVisitor)
+            } else {
+                deserializer.deserialize_u64(BytesVisitor)
+            }
+        }
+
+        pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_u64(*u as u64)
+        }
+    }
+
+    /// Serializes sizes with `exact_bytes`, e.g. "42MB".
+    pub mod si {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+        use crate::bytes::exact_bytes;
+
+        pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&exact_bytes(*u))
+        }
+    }
+
+    /// Serializes sizes with `exact_ibytes`, e.g. "42MiB".
+    pub mod iec {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+        use crate::bytes::exact_ibytes;
+
+        pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&exact_ibytes(*u))
+        }
+    }
+
+    /// Serializes sizes with `lossless_bytes`, e.g. "42.5MiB".
+    pub mod lossless {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+        use crate::bytes::lossless_bytes;
+
+        pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&lossless_bytes(*u))
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub mod serde_option {
+    use std::fmt::Formatter;
+
+    use serde_core::de::Visitor;
+    use serde_core::{Deserializer, Serializer};
+
+    use super::ibytes;
+    use super::serde::BytesVisitor;
+
+    /// Accepts null, or what `serde::deserialize` accepts, or `serde::raw` if
+    /// `raw` is set.
+    struct OptionVisitor {
+        raw: bool,
+    }
+
+    impl<'de> Visitor<'de> for OptionVisitor {
+        type Value = Option<usize>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            BytesVisitor.expecting(f)?;
+            write!(f, " or null")
+        }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            if self.raw {
+                super::serde::raw::deserialize(deserializer).map(Some)
+            } else {
+                super::serde::deserialize(deserializer).map(Some)
+            }
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<usize>, D::Error> {
+        deserializer.deserialize_option(OptionVisitor { raw: false })
+    }
+
+    pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+        match u {
+            Some(v) => s.serialize_some(&ibytes(*v)),
+            None => s.serialize_none(),
+        }
+    }
+
+    /// `json_schema` describes the sizes accepted by `deserialize`, to be used with
+    /// `#[schemars(schema_with = "humanize::bytes::serde_option::json_schema")]`.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        super::serde::schema(&["string", "number", "null"])
+    }
+
+    /// Serializes sizes as a number of bytes.
+    pub mod raw {
+        use serde_core::{Deserializer, Serializer};
+
+        use super::OptionVisitor;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<usize>, D::Error> {
+            deserializer.deserialize_option(OptionVisitor { raw: true })
+        }
+
+        pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+            match u {
+                Some(v) => s.serialize_some(&(*v as u64)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+
+    /// Serializes sizes with `exact_bytes`, e.g. "42MB".
+    pub mod si {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+
+        pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+            match u {
+                Some(v) => s.serialize_some(&crate::bytes::exact_bytes(*v)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+
+    /// Serializes sizes with `exact_ibytes`, e.g. "42MiB".
+    pub mod iec {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+
+        pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+            match u {
+                Some(v) => s.serialize_some(&crate::bytes::exact_ibytes(*v)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+
+    /// Serializes sizes with `lossless_bytes`, e.g. "42.5MiB".
+    pub mod lossless {
+        use serde_core::Serializer;
+
+        pub use super::deserialize;
+        #[cfg(feature = "schemars")]
+        pub use super::json_schema;
+
+        pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+            match u {
+                Some(v) => s.serialize_some(&crate::bytes::lossless_bytes(*v)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_size_spec {
+    use std::fmt::Formatter;
+
+    use serde_core::de::{Error, Visitor};
+    use serde_core::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::serde::BytesVisitor;
+    use super::{SizeSpec, parse_size_spec};
+
+    /// Accepts a number of bytes like `BytesVisitor`, or a string parsed with
+    /// `parse_size_spec`.
+    struct SizeSpecVisitor;
+
+    impl<'de> Visitor<'de> for SizeSpecVisitor {
+        type Value = SizeSpec;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(
+                f,
+                "a number of bytes, a size string like \"42MiB\" or a percentage like \"10%\""
+            )
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            BytesVisitor.visit_u64(v).map(SizeSpec::Absolute)
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            BytesVisitor.visit_i64(v).map(SizeSpec::Absolute)
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            BytesVisitor.visit_f64(v).map(SizeSpec::Absolute)
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse_size_spec(v).map_err(Error::custom)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SizeSpec {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_any(SizeSpecVisitor)
+            } else {
+                deserializer.deserialize_str(SizeSpecVisitor)
+            }
+        }
+    }
+
+    impl Serialize for SizeSpec {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            s.collect_str(self)
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    impl schemars::JsonSchema for SizeSpec {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            "SizeSpec".into()
+        }
+
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            "humanize::bytes::SizeSpec".into()
+        }
+
+        fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            let percent = r"^\s*([0-9]+(\.[0-9]*)?|\.[0-9]+)\s*%(\s+[oO][fF]\s+\S.*)?$";
+            schemars::json_schema!({
+                "description": "A size like \"512MiB\", a number of bytes, or a percentage like \"25% of memory\"",
+                "type": ["string", "number"],
+                "pattern": format!("{}|{}", super::serde::PATTERN, percent),
+                "examples": ["512MiB", 1048576, "10%", "25% of memory"],
+            })
+        }
+    }
+}
+
+/// clap value parsers for sizes and rates.
+///
+/// They don't report `possible_values`, clap would print examples as a closed
+/// "[possible values: ...]" list in `--help` and shell completion would offer
+/// nothing else. Hidden values don't help either, clap then prints an empty
+/// "[possible values: ]". Put examples in the help of the argument instead,
+/// invalid values already get a tip with the accepted units.
+#[cfg(feature = "clap")]
+pub mod clap {
+    use std::ffi::OsStr;
+
+    use clap::builder::TypedValueParser;
+    use clap::{Arg, Command, Error};
+
+    use super::{parse_bytes, parse_rate, parse_signed_bytes};
+    use crate::cli::{invalid_value, to_str};
+
+    const UNITS: &str = "B, kB, MB, GB, TB, PB, EB, KiB, MiB, GiB, TiB, PiB, EiB";
+    const PERIODS: &str = "s, m, h, d";
+
+    /// `BytesValueParser` parses sizes with `parse_bytes`, e.g. `--max-size 512MiB`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct BytesValueParser;
+
+    impl TypedValueParser for BytesValueParser {
+        type Value = usize;
+
+        fn parse_ref(
+            &self,
+            cmd: &Command,
+            arg: Option<&Arg>,
+            value: &OsStr,
+        ) -> Result<Self::Value, Error> {
+            let value = to_str(cmd, value)?;
+            parse_bytes(value).map_err(|err| {
+                let hint = format!(
+                    "valid units are {} (case-insensitive), e.g. 512KiB or 10GB",
+                    UNITS
+                );
+                invalid_value(cmd, arg, value, err, &hint)
+            })
+        }
+    }
+
+    /// `SignedBytesValueParser` parses size deltas with `parse_signed_bytes`,
+    /// e.g. `--grow=-512MiB`. Negative values need `Arg::allow_hyphen_values`,
+    /// or an `=` between the option and the value.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct SignedBytesValueParser;
+
+    impl TypedValueParser for SignedBytesValueParser {
+        type Value = i64;
+
+        fn parse_ref(
+            &self,
+            cmd: &Command,
+            arg: Option<&Arg>,
+            value: &OsStr,
+        ) -> Result<Self::Value, Error> {
+            let value = to_str(cmd, value)?;
+            parse_signed_bytes(value).map_err(|err| {
+                let hint = format!(
+                    "expected an optional sign and a size, valid units are {} (case-insensitive), e.g. +512MiB or -1GiB",
+                    UNITS
+                );
+                invalid_value(cmd, arg, value, err, &hint)
+            })
+        }
+    }
+
+    /// `RateValueParser` parses rates with `parse_rate`, e.g. `--limit 10MiB/s`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct RateValueParser;
+
+    impl TypedValueParser for RateValueParser {
+        type Value = usize;
+
+        fn parse_ref(
+            &self,
+            cmd: &Command,
+            arg: Option<&Arg>,
+            value: &OsStr,
+        ) -> Result<Self::Value, Error> {
+            let value = to_str(cmd, value)?;
+            parse_rate(value).map_err(|err| {
+                let hint = format!(
+                    "expected SIZE/PERIOD, valid units are {} and valid periods are {}, e.g. 10MiB/s",
+                    UNITS, PERIODS
+                );
+                invalid_value(cmd, arg, value, err, &hint)
+            })
+        }
+    }
+}
+
+/// `HumanBytes` is a `serde_with` adapter for sizes, it can be nested in
+/// `Vec`, `Option`, maps and so on. Sizes are serialized with `lossless_bytes`,
+/// and deserialized from strings, integers or floats like `bytes::serde`.
+/// `usize`, `u64`, `u32` and their `NonZero` counterparts are supported.
+///
+/// #[serde_as(as = "Vec<HumanBytes>")]
+/// sizes: Vec<u64>,
+#[cfg(feature = "serde_with")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HumanBytes;
+
+/// `BoundedBytes` is `HumanBytes` which rejects sizes outside `Bounds`, e.g.
+/// a buffer between 4KiB and 1GiB, in multiples of 4KiB.
+///
+/// #[serde_as(as = "BoundedBytes<4096, 1073741824, 4096>")]
+/// buffer: usize,
+#[cfg(feature = "serde_with")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BoundedBytes<
+    const MIN: usize = 0,
+    const MAX: usize = { usize::MAX },
+    const MULTIPLE: usize = 1,
+>;
+
+#[cfg(feature = "serde_with")]
+impl<const MIN: usize, const MAX: usize, const MULTIPLE: usize> BoundedBytes<MIN, MAX, MULTIPLE> {
+    /// BOUNDS are the bounds this adapter checks.
+    pub const BOUNDS: Bounds = Bounds::new().min(MIN).max(MAX).multiple_of(MULTIPLE);
+}
+
+#[cfg(feature = "serde_with")]
+mod serde_with_human_bytes {
+    use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 
     use serde_core::de::Error;
-    use serde_core::{Deserialize, Deserializer, Serializer};
+    use serde_core::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
 
-    use super::{ibytes, parse_bytes};
+    use super::{BoundedBytes, Bounds, HumanBytes, lossless_bytes};
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<usize, D::Error> {
-        let s: Cow<'de, str> = Deserialize::deserialize(deserializer)?;
-        parse_bytes(s.as_ref()).map_err(Error::custom)
+    fn serialize<S: Serializer>(size: Option<usize>, s: S) -> Result<S::Ok, S::Error> {
+        match size {
+            Some(size) => s.serialize_str(&lossless_bytes(size)),
+            None => Err(serde_core::ser::Error::custom("size is too large")),
+        }
     }
 
-    pub fn serialize<S: Serializer>(u: &usize, s: S) -> Result<S::Ok, S::Error> {
-        let b = ibytes(*u);
-        s.serialize_str(&b)
+    fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        bounds: &Bounds,
+    ) -> Result<usize, D::Error> {
+        let size = super::serde::deserialize(deserializer)?;
+        bounds.check(size).map_err(D::Error::custom)
     }
-}
 
-#[cfg(feature = "serde")]
-pub mod serde_option {
-    use std::borrow::Cow;
+    macro_rules! impl_human_bytes {
+        ($ty:ty, $get:expr, $new:expr) => {
+            impl_human_bytes!(HumanBytes, Bounds::new(), $ty, $get, $new);
+            impl_human_bytes!(
+                BoundedBytes<MIN, MAX, MULTIPLE>,
+                Self::BOUNDS,
+                $ty,
+                $get,
+                $new,
+                const MIN: usize,
+                const MAX: usize,
+                const MULTIPLE: usize
+            );
+        };
+        ($as:ty, $bounds:expr, $ty:ty, $get:expr, $new:expr $(, $($generics:tt)*)?) => {
+            impl<$($($generics)*)?> SerializeAs<$ty> for $as {
+                fn serialize_as<S: Serializer>(source: &$ty, s: S) -> Result<S::Ok, S::Error> {
+                    serialize(usize::try_from($get(*source)).ok(), s)
+                }
+            }
 
-    use serde_core::de::Error;
-    use serde_core::{Deserialize, Deserializer, Serializer};
+            impl<'de, $($($generics)*)?> DeserializeAs<'de, $ty> for $as {
+                fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<$ty, D::Error> {
+                    let size = deserialize(deserializer, &$bounds)?;
+                    $new(size).ok_or_else(|| {
+                        D::Error::custom(format!(
+                            "size {} is out of range for {}",
+                            size,
+                            stringify!($ty)
+                        ))
+                    })
+                }
+            }
+        };
+    }
 
-    use super::{ibytes, parse_bytes};
+    impl_human_bytes!(usize, |v| v, Some);
+    impl_human_bytes!(u64, |v| v, |v| u64::try_from(v).ok());
+    impl_human_bytes!(u32, |v| v, |v| u32::try_from(v).ok());
+    impl_human_bytes!(NonZeroUsize, NonZeroUsize::get, NonZeroUsize::new);
+    impl_human_bytes!(NonZeroU64, NonZeroU64::get, |v| u64::try_from(v)
+        .ok()
+        .and_then(NonZeroU64::new));
+    impl_human_bytes!(NonZeroU32, NonZeroU32::get, |v| u32::try_from(v)
+        .ok()
+        .and_then(NonZeroU32::new));
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(
-        deserializer: D,
-    ) -> Result<Option<usize>, D::Error> {
-        let s: Option<Cow<'de, str>> = Deserialize::deserialize(deserializer)?;
+    #[cfg(feature = "schemars")]
+    impl<T> serde_with::schemars_1::JsonSchemaAs<T> for HumanBytes {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            "HumanBytes".into()
+        }
 
-        match s {
-            None => Ok(None),
-            Some(s) => {
-                let size = parse_bytes(s.as_ref()).map_err(Error::custom)?;
-                Ok(Some(size))
-            }
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            "humanize::bytes::HumanBytes".into()
+        }
+
+        fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            super::serde::json_schema(generator)
         }
     }
 
-    pub fn serialize<S: Serializer>(u: &Option<usize>, s: S) -> Result<S::Ok, S::Error> {
-        match u {
-            Some(v) => s.serialize_str(ibytes(*v).as_str()),
-            None => s.serialize_none(),
+    // Bounds can't be expressed on strings, so this is the same schema
+    #[cfg(feature = "schemars")]
+    impl<T, const MIN: usize, const MAX: usize, const MULTIPLE: usize>
+        serde_with::schemars_1::JsonSchemaAs<T> for BoundedBytes<MIN, MAX, MULTIPLE>
+    {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            <HumanBytes as serde_with::schemars_1::JsonSchemaAs<T>>::schema_name()
+        }
+
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            <HumanBytes as serde_with::schemars_1::JsonSchemaAs<T>>::schema_id()
+        }
+
+        fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            super::serde::json_schema(generator)
         }
     }
 }
@@ -232,6 +1791,386 @@ mod tests {
         }
     }
 
+    #[test]
+    fn size_spec() {
+        let tests = [
+            ("512MiB", SizeSpec::Absolute(512 * MIBYTE)),
+            ("42", SizeSpec::Absolute(42)),
+            ("10%", SizeSpec::Relative(0.1)),
+            (" 10 % ", SizeSpec::Relative(0.1)),
+            ("12.5%", SizeSpec::Relative(0.125)),
+            ("25% of memory", SizeSpec::Relative(0.25)),
+            ("25% OF disk", SizeSpec::Relative(0.25)),
+        ];
+
+        for (input, want) in tests {
+            let got = parse_size_spec(input).unwrap();
+            assert_eq!(got, want, "input: {input}");
+        }
+
+        for input in ["10% memory", "10% of", "10% often", "10 Qb"] {
+            assert!(parse_size_spec(input).is_err(), "input: {input}");
+        }
+
+        for input in [
+            "%",
+            "abc%",
+            "-5%",
+            "-10%",
+            "+10%",
+            "inf%",
+            "NaN%",
+            "1e2%",
+            &format!("{}%", "9".repeat(400)),
+        ] {
+            let err = parse_size_spec(input).unwrap_err();
+            assert!(
+                matches!(err, Error::InvalidPercent { .. }),
+                "input: {input}, err: {err}"
+            );
+        }
+        assert_eq!(
+            parse_size_spec("-5% of memory").unwrap_err().to_string(),
+            "invalid percentage \"-5%\", expected a plain decimal like 12.5%"
+        );
+
+        assert_eq!(SizeSpec::Absolute(4096).resolve(GIBYTE), 4096);
+        assert_eq!(SizeSpec::Relative(0.25).resolve(GIBYTE), 256 * MIBYTE);
+        assert_eq!(SizeSpec::Relative(0.1).to_string(), "10%");
+        for input in ["7%", "12.5%", "0.3%", "33.333%", "0%", "100%", "250%"] {
+            let spec = parse_size_spec(input).unwrap();
+            assert_eq!(spec.to_string(), input);
+        }
+        assert_eq!(SizeSpec::Absolute(4096).to_string(), "4096");
+    }
+
+    #[test]
+    fn rate() {
+        let tests = [
+            ("10MiB/s", 10 * MIBYTE),
+            ("10 MiB / s", 10 * MIBYTE),
+            ("1.5 GB/min", 25 * MBYTE),
+            ("36k/h", 10),
+            ("86400/d", 1),
+            ("1GB/d", 11574),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(parse_rate(input).unwrap(), want, "input: {input}");
+        }
+
+        for input in ["10MiB", "10MiB/w", "10XB/s", "/s"] {
+            assert!(parse_rate(input).is_err(), "input: {input}");
+        }
+
+        let tests = [
+            (
+                "10MiB",
+                "invalid rate \"10MiB\", expected SIZE/PERIOD of at least 1B/s",
+            ),
+            (
+                "1/d",
+                "invalid rate \"1/d\", expected SIZE/PERIOD of at least 1B/s",
+            ),
+            (
+                "86399B/d",
+                "invalid rate \"86399B/d\", expected SIZE/PERIOD of at least 1B/s",
+            ),
+            (
+                "0B/s",
+                "invalid rate \"0B/s\", expected SIZE/PERIOD of at least 1B/s",
+            ),
+        ];
+        for (input, want) in tests {
+            assert_eq!(
+                parse_rate(input).unwrap_err().to_string(),
+                want,
+                "input: {input}"
+            );
+        }
+    }
+
+    #[cfg(feature = "env")]
+    #[test]
+    fn env() {
+        let source = [("CACHE", "512MiB"), ("BAD", "512XB"), ("EMPTY", "")];
+
+        assert_eq!(from_source(&source, "CACHE").unwrap(), 512 * MIBYTE);
+        assert_eq!(from_source_or(&source, "CACHE", 1).unwrap(), 512 * MIBYTE);
+        assert_eq!(from_source_or(&source, "EMPTY", 1).unwrap(), 1);
+        assert_eq!(from_source_or(&source, "UNSET", 1).unwrap(), 1);
+
+        let err = from_source_or(&source, "BAD", 1).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid BAD=\"512XB\": unknown unit \"XB\""
+        );
+        let err = from_source(&source, "UNSET").unwrap_err();
+        assert_eq!(err.to_string(), "UNSET is not set");
+    }
+
+    #[test]
+    #[should_panic(expected = "bounds min is greater than max")]
+    fn inverted_bounds() {
+        let _ = Bounds::new().min(GIBYTE).max(KIBYTE);
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::new()
+            .min(4 * KIBYTE)
+            .max(GIBYTE)
+            .multiple_of(4 * KIBYTE);
+
+        assert_eq!(bounds.parse("4KiB").unwrap(), 4 * KIBYTE);
+        assert_eq!(bounds.parse("1GiB").unwrap(), GIBYTE);
+        assert!(matches!(
+            bounds.parse("1XB"),
+            Err(Error::UnknownUnit { .. })
+        ));
+
+        let tests = [
+            ("2GiB", "must be at most 1GiB, got 2GiB"),
+            ("1KiB", "must be at least 4KiB, got 1KiB"),
+            ("5KiB", "must be a multiple of 4KiB, got 5KiB"),
+            ("4.5kB", "must be a multiple of 4KiB, got 4.5kB"),
+        ];
+        for (input, want) in tests {
+            let err = bounds.parse(input).unwrap_err();
+            assert_eq!(err.to_string(), want, "input: {input}");
+        }
+
+        assert_eq!(Bounds::default().check(usize::MAX), Ok(usize::MAX));
+        assert_eq!(Bounds::new().multiple_of(0).check(7), Ok(7));
+    }
+
+    #[test]
+    fn signed() {
+        let tests = [
+            ("-512MiB", -512 * MIBYTE as i64),
+            ("+1GB", GBYTE as i64),
+            ("1GB", GBYTE as i64),
+            ("-0", 0),
+            ("-8EiB", i64::MIN),
+            ("+1.5e3", 1500),
+        ];
+        for (input, want) in tests {
+            assert_eq!(parse_signed_bytes(input).unwrap(), want, "input: {input}");
+        }
+
+        for input in ["8EiB", "-8EiB1", "-9EiB", "16EiB"] {
+            assert!(parse_signed_bytes(input).is_err(), "input: {input}");
+        }
+        for input in ["--1", "+-1", "- 1", "-", "+"] {
+            assert!(parse_signed_bytes(input).is_err(), "input: {input}");
+        }
+
+        let tests = [
+            (-300000000, Sign::Negative, "-300MB"),
+            (-300000000, Sign::Always, "-300MB"),
+            (1200000000, Sign::Negative, "1.2GB"),
+            (1200000000, Sign::Always, "+1.2GB"),
+            (0, Sign::Always, "0B"),
+            (i64::MIN, Sign::Always, "-9.2EB"),
+        ];
+        for (size, sign, want) in tests {
+            assert_eq!(signed_bytes(size, sign), want, "size: {size}");
+        }
+        assert_eq!(signed_ibytes(1288490189, Sign::Always), "+1.2GiB");
+        assert_eq!(signed_ibytes(-1024, Sign::default()), "-1KiB");
+
+        let tests = [
+            ("-15EiB", -15 * EIBYTE as i128),
+            ("+16EB", 16 * EBYTE as i128),
+            ("-9EiB", -9 * EIBYTE as i128),
+        ];
+        for (input, want) in tests {
+            assert_eq!(
+                parse_signed_bytes_i128(input).unwrap(),
+                want,
+                "input: {input}"
+            );
+        }
+        for input in ["-16EiB", "--1", "+"] {
+            assert!(parse_signed_bytes_i128(input).is_err(), "input: {input}");
+        }
+
+        let tests = [
+            (-(u64::MAX as i128), Sign::Negative, "-18.4EB"),
+            (u64::MAX as i128, Sign::Always, "+18.4EB"),
+            (
+                i128::MIN,
+                Sign::Negative,
+                "-170141183460469231731687303715884105728B",
+            ),
+            (
+                i128::MAX,
+                Sign::Always,
+                "+170141183460469231731687303715884105727B",
+            ),
+            (
+                999 * EBYTE as i128 + 900 * PBYTE as i128,
+                Sign::Negative,
+                "999.9EB",
+            ),
+            (
+                1000 * EBYTE as i128,
+                Sign::Negative,
+                "1000000000000000000000B",
+            ),
+        ];
+        for (size, sign, want) in tests {
+            assert_eq!(signed_bytes_i128(size, sign), want, "size: {size}");
+        }
+        assert_eq!(
+            signed_ibytes_i128(-(u64::MAX as i128), Sign::Always),
+            "-16EiB"
+        );
+        assert_eq!(signed_ibytes_i128((1 << 70) - 1, Sign::Always), "+1024EiB");
+        assert_eq!(
+            signed_ibytes_i128(1 << 70, Sign::Always),
+            "+1180591620717411303424B"
+        );
+        assert_eq!(
+            signed_ibytes_i128(i128::MIN, Sign::Always),
+            "-170141183460469231731687303715884105728B"
+        );
+    }
+
+    #[test]
+    fn size_range() {
+        use Bound::{Excluded, Included, Unbounded};
+
+        let tests = [
+            (
+                "1KiB..4MiB",
+                Included(KIBYTE),
+                Excluded(4 * MIBYTE),
+                "1KiB..4MiB",
+            ),
+            (
+                "1KiB..=4MiB",
+                Included(KIBYTE),
+                Included(4 * MIBYTE),
+                "1KiB..=4MiB",
+            ),
+            (
+                "1KiB-4MiB",
+                Included(KIBYTE),
+                Included(4 * MIBYTE),
+                "1KiB..=4MiB",
+            ),
+            (
+                " 1 KiB - 4 MiB ",
+                Included(KIBYTE),
+                Included(4 * MIBYTE),
+                "1KiB..=4MiB",
+            ),
+            (
+                "1e-3MB-1.5e3",
+                Included(KBYTE),
+                Included(1500),
+                "1kB..=1.5kB",
+            ),
+            (">=10GB", Included(10 * GBYTE), Unbounded, ">=10GB"),
+            (">10GB", Excluded(10 * GBYTE), Unbounded, ">10GB"),
+            ("<512B", Unbounded, Excluded(512), "<512B"),
+            ("<= 1 GiB", Unbounded, Included(GIBYTE), "<=1GiB"),
+            ("1MiB..", Included(MIBYTE), Unbounded, ">=1MiB"),
+            ("..1MiB", Unbounded, Excluded(MIBYTE), "<1MiB"),
+            ("..=1MiB", Unbounded, Included(MIBYTE), "<=1MiB"),
+            ("..", Unbounded, Unbounded, ".."),
+            ("1.5KB", Included(1500), Included(1500), "1.5kB"),
+            ("0B..0B", Included(0), Excluded(0), "0B..0B"),
+        ];
+
+        for (input, start, end, want) in tests {
+            let range = parse_size_range(input).unwrap();
+            assert_eq!((range.start(), range.end()), (start, end), "input: {input}");
+            assert_eq!(range.to_string(), want, "input: {input}");
+            assert_eq!(parse_size_range(want).unwrap(), range, "input: {input}");
+        }
+
+        let range = parse_size_range("1MiB..1GiB").unwrap();
+        assert!(range.contains(MIBYTE));
+        assert!(range.contains(GIBYTE - 1));
+        assert!(!range.contains(GIBYTE));
+        assert!(!range.contains(MIBYTE - 1));
+        assert!(parse_size_range(">1KiB").unwrap().contains(KIBYTE + 1));
+        assert!(!parse_size_range(">1KiB").unwrap().contains(KIBYTE));
+
+        let range = SizeRange::new(Excluded(KIBYTE), Included(MIBYTE));
+        assert_eq!(range.to_string(), "1025B..=1MiB");
+        assert!(parse_size_range("1KiB..1KiB").unwrap().is_empty());
+        assert!(!parse_size_range("1KiB..=1KiB").unwrap().is_empty());
+
+        // Ranges built directly, including empty and inverted ones, are
+        // formatted as a range of the same sizes
+        let sizes = [0, 5, 6, 7, KIBYTE, usize::MAX - 1, usize::MAX];
+        let bounds = sizes
+            .iter()
+            .flat_map(|&size| [Included(size), Excluded(size)])
+            .chain([Unbounded]);
+        for start in bounds.clone() {
+            for end in bounds.clone() {
+                let range = SizeRange::new(start, end);
+                let formatted = range.to_string();
+                let parsed = parse_size_range(&formatted)
+                    .unwrap_or_else(|err| panic!("{range:?} as {formatted}: {err}"));
+                assert_eq!(parsed.is_empty(), range.is_empty(), "{range:?}");
+                for size in sizes {
+                    assert_eq!(
+                        parsed.contains(size),
+                        range.contains(size),
+                        "{range:?} as {formatted}, size: {size}"
+                    );
+                }
+            }
+        }
+
+        for input in [
+            "4MiB-1KiB",
+            "2..1",
+            "1KiB..=",
+            "1KiB-",
+            "-1KiB",
+            "1XB-2KB",
+            "<",
+            ">=x",
+            "1--2",
+        ] {
+            assert!(parse_size_range(input).is_err(), "input: {input}");
+        }
+        let err = parse_size_range("4MiB..1KiB").unwrap_err();
+        assert_eq!(err.to_string(), "invalid range \"4MiB..1KiB\"");
+    }
+
+    #[test]
+    fn compare() {
+        // Same order as `sort -h`
+        let want = [
+            "-2M", "-1K", "-5", "", "0", "abc", "1e3", "1m", "10", "512", "0.5K", "001K", "1KiB",
+            "1k", "2K", " 3K", "1M", "1.50G", "1.5G", "1Y", "1Q",
+        ];
+
+        let mut got = want.to_vec();
+        got.reverse();
+        got.sort_by(|a, b| compare_human(a, b));
+        assert_eq!(got, want);
+
+        let mut got: Vec<String> = want.iter().rev().map(|s| s.to_string()).collect();
+        got.sort_by_cached_key(|s| human_key(s));
+        assert_eq!(got, want);
+
+        assert_eq!(compare_human("1.5GiB", "1G"), Ordering::Greater);
+        assert_eq!(compare_human("-0", "0"), Ordering::Less);
+        assert_eq!(compare_human(&bytes(999), &bytes(1000)), Ordering::Less);
+        assert_eq!(
+            compare_human(&ibytes(GIBYTE), &ibytes(GIBYTE)),
+            Ordering::Equal
+        );
+    }
+
     #[test]
     fn stringify() {
         let tests = [
@@ -287,4 +2226,491 @@ mod tests {
             assert_eq!(got, want, "want in {name:?}, got {got}, want {want}");
         }
     }
+
+    #[test]
+    fn exact() {
+        let tests = [
+            (exact_bytes(0), "0B"),
+            (exact_bytes(1500), "1500B"),
+            (exact_bytes(42 * MBYTE), "42MB"),
+            (exact_bytes(3 * EBYTE), "3EB"),
+            (exact_ibytes(44564480), "43520KiB"),
+            (exact_ibytes(KIBYTE), "1KiB"),
+            (exact_ibytes(1000), "1000B"),
+            (lossless_bytes(0), "0B"),
+            (lossless_bytes(1000), "1kB"),
+            (lossless_bytes(1024), "1KiB"),
+            (lossless_bytes(1500), "1.5kB"),
+            (lossless_bytes(44564480), "42.5MiB"),
+            (lossless_bytes(44564481), "44564481B"),
+            (lossless_bytes(1005030000), "1.00503GB"),
+            (lossless_bytes(usize::MAX), "18446744073709551615B"),
+        ];
+
+        for (got, want) in tests {
+            assert_eq!(got, want);
+        }
+
+        for size in (0..64).flat_map(|i| {
+            [
+                1 << i,
+                (1 << i) - 1,
+                (1usize << i).saturating_add(1 << i >> 1),
+                10usize.pow(i % 20).saturating_mul(7),
+            ]
+        }) {
+            for s in [exact_bytes(size), exact_ibytes(size), lossless_bytes(size)] {
+                assert_eq!(parse_bytes(&s).unwrap(), size, "input: {s}");
+            }
+        }
+    }
+
+    #[test]
+    fn parse_exact() {
+        assert_eq!(parse_bytes("9007199254740993").unwrap(), 9007199254740993);
+        assert_eq!(
+            parse_bytes("8796093022207KiB").unwrap(),
+            8796093022207 * KIBYTE
+        );
+        assert_eq!(parse_bytes("1.5").unwrap(), 1);
+        assert_eq!(parse_bytes(".5k").unwrap(), 500);
+        assert!(matches!(parse_bytes("16EiB"), Err(Error::TooLarge { .. })));
+        assert!(matches!(
+            parse_bytes("99999999999999999999"),
+            Err(Error::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn parse_exponent() {
+        let tests = [
+            ("1e6", 1000000),
+            ("1.5E3 KB", 1500000),
+            ("1.5e+3KB", 1500000),
+            ("1e-3 MB", 1000),
+            ("12.5e-1k", 1250),
+            (".5e1", 5),
+            ("1.5e-1", 0),
+            ("1e19", 10000000000000000000),
+            ("0e99999999999999999999", 0),
+            ("1e-99999999999999999999", 0),
+            ("2^30", GIBYTE),
+            ("2^10 KiB", MIBYTE),
+            ("2^63", 1 << 63),
+            ("0^0", 1),
+            ("0^5 GB", 0),
+            ("1^99999999999999999999", 1),
+            // Without digits, "e" is the exabyte unit
+            ("1e", EBYTE),
+            ("1 e", EBYTE),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(parse_bytes(input).unwrap(), want, "input: {input}");
+        }
+
+        for input in ["2e19", "1e1000", "1.5e18 KiB", "2^64", "2^10 EiB", "10^20"] {
+            assert!(
+                matches!(parse_bytes(input), Err(Error::TooLarge { .. })),
+                "input: {input}"
+            );
+        }
+        for input in ["1.5^2", "^2", "1e+", "1e3.5", "2^-1", "e5"] {
+            assert!(parse_bytes(input).is_err(), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn parse_const() {
+        const DEFAULT_BUF: usize = crate::bytes!("64KiB");
+        assert_eq!(DEFAULT_BUF, 64 * KIBYTE);
+        assert_eq!(crate::bytes!("2^20"), MIBYTE);
+        assert_eq!(crate::bytes!("1.5 kB"), 1500);
+
+        for input in [
+            "42",
+            "42.5 MiB",
+            "1005.03 MB",
+            ".5k",
+            "1.",
+            "12.5 EiB",
+            "9007199254740993",
+            "1.12345678901234567890123GiB",
+            "1.5E3 KB",
+            "1e-3 MB",
+            "2^30",
+            "1e",
+            "1e1000",
+            "2^64",
+            "1.5^2",
+            "e5",
+            " 1 KiB ",
+            "1 KiB\t",
+            "",
+            ".",
+            "1.2.3",
+            "1 XB",
+            "-1",
+            "16EiB",
+            "99999999999999999999",
+        ] {
+            assert_eq!(
+                parse_bytes_const(input),
+                parse_bytes(input).ok(),
+                "input: {input:?}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        #[derive(Debug, PartialEq, ::serde::Deserialize)]
+        struct Config {
+            #[serde(with = "super::serde")]
+            size: usize,
+            #[serde(with = "super::serde_option", default)]
+            limit: Option<usize>,
+        }
+
+        let tests = [
+            (r#"{"size": "42 MiB"}"#, 42 * MIBYTE, None),
+            (r#"{"size": 1048576}"#, MIBYTE, None),
+            (r#"{"size": 1.5e3, "limit": null}"#, 1500, None),
+            (r#"{"size": 0, "limit": "1KiB"}"#, 0, Some(KIBYTE)),
+            (r#"{"size": "1k", "limit": 512}"#, KBYTE, Some(512)),
+        ];
+
+        for (input, size, limit) in tests {
+            let got: Config = serde_json::from_str(input).unwrap();
+            assert_eq!(got, Config { size, limit }, "input: {input}");
+        }
+
+        for input in [
+            r#"{"size": -1}"#,
+            r#"{"size": -1.5}"#,
+            r#"{"size": 1.5}"#,
+            r#"{"size": "1 XB"}"#,
+            r#"{"size": true}"#,
+            r#"{"size": 1, "limit": -1}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Config>(input).is_err(),
+                "input: {input}"
+            );
+        }
+
+        for (input, want) in [
+            ("1024", SizeSpec::Absolute(KIBYTE)),
+            ("1.5e3", SizeSpec::Absolute(1500)),
+            (r#""512MiB""#, SizeSpec::Absolute(512 * MIBYTE)),
+            (r#""25% of memory""#, SizeSpec::Relative(0.25)),
+        ] {
+            let got: SizeSpec = serde_json::from_str(input).unwrap();
+            assert_eq!(got, want, "input: {input}");
+        }
+        for input in ["-1", "1.5", "true", r#""-5%""#] {
+            assert!(
+                serde_json::from_str::<SizeSpec>(input).is_err(),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde(with = "super::serde::raw")]
+            raw: usize,
+            #[serde(with = "super::serde::si")]
+            si: usize,
+            #[serde(with = "super::serde::iec")]
+            iec: usize,
+            #[serde(with = "super::serde::lossless")]
+            lossless: usize,
+            #[serde(with = "super::serde_option::lossless")]
+            limit: Option<usize>,
+        }
+
+        let sizes = [
+            0,
+            1,
+            999,
+            1500,
+            44564480,
+            44564481,
+            1005030000,
+            5 * TIBYTE + 1,
+        ];
+        for size in sizes {
+            let config = Config {
+                raw: size,
+                si: size,
+                iec: size,
+                lossless: size,
+                limit: Some(size),
+            };
+
+            let json = serde_json::to_string(&config).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Config>(&json).unwrap(),
+                config,
+                "json: {json}"
+            );
+
+            let toml = toml::to_string(&config).unwrap();
+            assert_eq!(
+                toml::from_str::<Config>(&toml).unwrap(),
+                config,
+                "toml: {toml}"
+            );
+
+            let encoded = bincode::serialize(&config).unwrap();
+            assert_eq!(
+                bincode::deserialize::<Config>(&encoded).unwrap(),
+                config,
+                "size: {size}"
+            );
+        }
+
+        let config = Config {
+            raw: 44564480,
+            si: 44564480,
+            iec: 44564480,
+            lossless: 44564480,
+            limit: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&config).unwrap(),
+            r#"{"raw":44564480,"si":"44564480B","iec":"43520KiB","lossless":"42.5MiB","limit":null}"#
+        );
+
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Raw {
+            #[serde(with = "super::serde_option::raw")]
+            limit: Option<usize>,
+            #[serde(with = "super::serde_option")]
+            quota: Option<usize>,
+        }
+        for (limit, quota) in [(Some(42), None), (None, Some(MIBYTE))] {
+            let raw = Raw { limit, quota };
+            let encoded = bincode::serialize(&raw).unwrap();
+            assert_eq!(bincode::deserialize::<Raw>(&encoded).unwrap(), raw);
+        }
+    }
+
+    #[cfg(feature = "serde_with")]
+    #[test]
+    fn serde_with() {
+        use std::collections::BTreeMap;
+        use std::num::{NonZeroU32, NonZeroU64};
+
+        use ::serde_with::serde_as;
+
+        #[serde_as]
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde_as(as = "Vec<HumanBytes>")]
+            sizes: Vec<u64>,
+            #[serde_as(as = "BTreeMap<_, HumanBytes>")]
+            limits: BTreeMap<String, u32>,
+            #[serde_as(as = "Option<Option<HumanBytes>>")]
+            quota: Option<Option<NonZeroU64>>,
+            #[serde_as(as = "Option<Vec<HumanBytes>>")]
+            buffers: Option<Vec<NonZeroU32>>,
+        }
+
+        let got: Config = serde_json::from_str(
+            r#"{"sizes": ["1KiB", 2048, 1.5e3], "limits": {"a": "1MB"}, "quota": "42.5MiB", "buffers": [1]}"#,
+        )
+        .unwrap();
+        let want = Config {
+            sizes: vec![1024, 2048, 1500],
+            limits: BTreeMap::from([("a".to_string(), 1000000)]),
+            quota: Some(NonZeroU64::new(44564480)),
+            buffers: Some(vec![NonZeroU32::MIN]),
+        };
+        assert_eq!(got, want);
+
+        let json = serde_json::to_string(&want).unwrap();
+        assert_eq!(
+            json,
+            r#"{"sizes":["1KiB","2KiB","1.5kB"],"limits":{"a":"1MB"},"quota":"42.5MiB","buffers":["1B"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), want);
+
+        for input in [
+            r#"{"sizes": [], "limits": {"a": "5GiB"}, "quota": null, "buffers": null}"#,
+            r#"{"sizes": [], "limits": {}, "quota": 0, "buffers": null}"#,
+            r#"{"sizes": [], "limits": {}, "quota": null, "buffers": ["0B"]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Config>(input).is_err(),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[cfg(feature = "serde_with")]
+    #[test]
+    fn serde_with_bounded() {
+        use ::serde_with::serde_as;
+
+        #[serde_as]
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde_as(as = "BoundedBytes<4096, 1073741824, 4096>")]
+            buffer: usize,
+            #[serde_as(as = "Option<BoundedBytes<1024>>")]
+            cache: Option<u64>,
+        }
+
+        let got: Config = serde_json::from_str(r#"{"buffer": "64KiB", "cache": 1024}"#).unwrap();
+        let want = Config {
+            buffer: 64 * KIBYTE,
+            cache: Some(1024),
+        };
+        assert_eq!(got, want);
+        assert_eq!(
+            serde_json::to_string(&want).unwrap(),
+            r#"{"buffer":"64KiB","cache":"1KiB"}"#
+        );
+
+        let tests = [
+            (
+                r#"{"buffer": "2GiB", "cache": null}"#,
+                "must be at most 1GiB, got 2GiB",
+            ),
+            (
+                r#"{"buffer": 5000, "cache": null}"#,
+                "must be a multiple of 4KiB, got 5kB",
+            ),
+            (
+                r#"{"buffer": "4KiB", "cache": "1kB"}"#,
+                "must be at least 1KiB, got 1kB",
+            ),
+        ];
+        for (input, want) in tests {
+            let err = serde_json::from_str::<Config>(input).unwrap_err();
+            assert!(
+                err.to_string().starts_with(want),
+                "input: {input}, err: {err}"
+            );
+        }
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn clap() {
+        use ::clap::error::ErrorKind;
+        use ::clap::{Arg, Command};
+
+        use super::clap::{BytesValueParser, RateValueParser, SignedBytesValueParser};
+
+        let cmd = Command::new("test")
+            .arg(
+                Arg::new("size")
+                    .long("max-size")
+                    .value_parser(BytesValueParser),
+            )
+            .arg(Arg::new("rate").long("limit").value_parser(RateValueParser));
+
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(["test", "--max-size", "42 MiB", "--limit", "1KiB/s"])
+            .unwrap();
+        assert_eq!(matches.get_one::<usize>("size"), Some(&(42 * MIBYTE)));
+        assert_eq!(matches.get_one::<usize>("rate"), Some(&KIBYTE));
+
+        let err = cmd
+            .clone()
+            .try_get_matches_from(["test", "--max-size", "42 XB"])
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+        let message = err.to_string();
+        assert!(
+            message.contains("invalid value '42 XB' for '--max-size <size>'"),
+            "{message}"
+        );
+        assert!(message.contains("KiB, MiB"), "{message}");
+        assert!(message.contains("e.g. 512KiB"), "{message}");
+
+        let help = cmd.clone().render_help().to_string();
+        assert!(!help.contains("possible values"), "{help}");
+
+        let err = cmd
+            .try_get_matches_from(["test", "--limit", "1KiB"])
+            .unwrap_err();
+        assert!(err.to_string().contains("SIZE/PERIOD"), "{err}");
+
+        let cmd = Command::new("test").arg(
+            Arg::new("grow")
+                .long("grow")
+                .allow_hyphen_values(true)
+                .value_parser(SignedBytesValueParser),
+        );
+        for (arg, want) in [("-512MiB", -512 * MIBYTE as i64), ("+1GB", GBYTE as i64)] {
+            let matches = cmd
+                .clone()
+                .try_get_matches_from(["test", "--grow", arg])
+                .unwrap();
+            assert_eq!(matches.get_one::<i64>("grow"), Some(&want));
+        }
+
+        let err = cmd
+            .try_get_matches_from(["test", "--grow", "-16EiB"])
+            .unwrap_err();
+        assert!(err.to_string().contains("too large"), "{err}");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        #[derive(::schemars::JsonSchema)]
+        #[allow(dead_code)]
+        struct Config {
+            #[schemars(schema_with = "super::serde::json_schema")]
+            size: usize,
+            #[schemars(schema_with = "super::serde_option::lossless::json_schema")]
+            limit: Option<usize>,
+            quota: SizeSpec,
+        }
+
+        let schema = ::schemars::schema_for!(Config);
+        let properties = &schema.as_value()["properties"];
+
+        assert_eq!(
+            properties["size"]["type"],
+            serde_json::json!(["string", "number"])
+        );
+        assert_eq!(properties["size"]["pattern"], super::serde::PATTERN);
+        assert_eq!(properties["size"]["examples"][0], "512MiB");
+        assert_eq!(
+            properties["limit"]["type"],
+            serde_json::json!(["string", "number", "null"])
+        );
+        assert_eq!(properties["quota"]["$ref"], "#/$defs/SizeSpec");
+        assert_eq!(
+            schema.as_value()["$defs"]["SizeSpec"]["type"],
+            serde_json::json!(["string", "number"])
+        );
+    }
+
+    #[cfg(all(feature = "schemars", feature = "serde_with"))]
+    #[test]
+    fn json_schema_as() {
+        #[::serde_with::serde_as]
+        #[derive(::serde::Serialize, ::schemars::JsonSchema)]
+        struct Config {
+            #[serde_as(as = "Vec<HumanBytes>")]
+            sizes: Vec<u64>,
+        }
+
+        let schema = ::schemars::schema_for!(Config);
+        let sizes = &schema.as_value()["properties"]["sizes"];
+        assert_eq!(sizes["type"], "array");
+        assert_eq!(sizes["items"]["$ref"], "#/$defs/HumanBytes");
+    }
 }
@@ -0,0 +1,1381 @@
+// Port from GNU coreutils' numfmt
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const SUFFIXES: [u8; 8] = *b"KMGTPEZY";
+
+// Unscaled output of larger numbers can't be printed reliably
+const MAX_UNSCALED_DIGITS: u32 = 18;
+// Values larger than 999Y are rejected
+const MAX_ACCEPTABLE_DIGITS: u32 = 27;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidNumber {
+        input: String,
+    },
+    InvalidSuffix {
+        input: String,
+    },
+    TrailingSuffix {
+        input: String,
+        suffix: String,
+    },
+    RejectingSuffix {
+        input: String,
+    },
+    MissingISuffix {
+        input: String,
+    },
+    TooLargeToConvert {
+        input: String,
+    },
+    TooLargeToPrint {
+        value: String,
+        precision: usize,
+    },
+    TooLargeToHandle {
+        value: String,
+    },
+    InvalidFormat {
+        format: String,
+        reason: &'static str,
+    },
+    InvalidArgument {
+        option: &'static str,
+        value: String,
+    },
+    GroupingWithScale,
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidNumber { input } => write!(f, "invalid number: '{}'", input),
+            Error::InvalidSuffix { input } => write!(f, "invalid suffix in input: '{}'", input),
+            Error::TrailingSuffix { input, suffix } => {
+                write!(f, "invalid suffix in input '{}': '{}'", input, suffix)
+            }
+            Error::RejectingSuffix { input } => write!(
+                f,
+                "rejecting suffix in input: '{}' (consider using --from)",
+                input
+            ),
+            Error::MissingISuffix { input } => {
+                write!(f, "missing 'i' suffix in input: '{}' (e.g Ki/Mi/Gi)", input)
+            }
+            Error::TooLargeToConvert { input } => {
+                write!(f, "value too large to be converted: '{}'", input)
+            }
+            Error::TooLargeToPrint {
+                value,
+                precision: 0,
+            } => write!(
+                f,
+                "value too large to be printed: '{}' (consider using --to)",
+                value
+            ),
+            Error::TooLargeToPrint { value, precision } => write!(
+                f,
+                "value/precision too large to be printed: '{}/{}' (consider using --to)",
+                value, precision
+            ),
+            Error::TooLargeToHandle { value } => write!(
+                f,
+                "value too large to be printed: '{}' (cannot handle values > 999Y)",
+                value
+            ),
+            Error::InvalidFormat { format, reason } => {
+                write!(f, "{}", reason.replace("{}", &format!("'{}'", format)))
+            }
+            Error::InvalidArgument { option, value } => {
+                write!(f, "invalid argument '{}' for '--{}'", value, option)
+            }
+            Error::GroupingWithScale => write!(f, "grouping cannot be combined with --to"),
+        }
+    }
+}
+
+/// `FromScale` is the scale of the input, the same as `numfmt --from`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum FromScale {
+    /// No auto-scaling is done, suffixes will trigger an error
+    #[default]
+    None,
+    /// 1K = 1000, 1Ki = 1024, 1M = 1000000, 1Mi = 1048576
+    Auto,
+    /// 1K = 1000, 1M = 1000000
+    Si,
+    /// 1K = 1024, 1M = 1048576
+    Iec,
+    /// 1Ki = 1024, 1Mi = 1048576
+    IecI,
+}
+
+impl FromStr for FromScale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(FromScale::None),
+            "auto" => Ok(FromScale::Auto),
+            "si" => Ok(FromScale::Si),
+            "iec" => Ok(FromScale::Iec),
+            "iec-i" => Ok(FromScale::IecI),
+            _ => Err(Error::InvalidArgument {
+                option: "from",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// `ToScale` is the scale of the output, the same as `numfmt --to`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ToScale {
+    #[default]
+    None,
+    Si,
+    Iec,
+    IecI,
+}
+
+impl ToScale {
+    fn base(&self) -> u64 {
+        match self {
+            ToScale::None => 1,
+            ToScale::Si => 1000,
+            ToScale::Iec | ToScale::IecI => 1024,
+        }
+    }
+}
+
+impl FromStr for ToScale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(ToScale::None),
+            "si" => Ok(ToScale::Si),
+            "iec" => Ok(ToScale::Iec),
+            "iec-i" => Ok(ToScale::IecI),
+            _ => Err(Error::InvalidArgument {
+                option: "to",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Round is the rounding method, the same as `numfmt --round`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Round {
+    Up,
+    Down,
+    #[default]
+    FromZero,
+    TowardsZero,
+    Nearest,
+}
+
+impl FromStr for Round {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Round::Up),
+            "down" => Ok(Round::Down),
+            "from-zero" => Ok(Round::FromZero),
+            "towards-zero" => Ok(Round::TowardsZero),
+            "nearest" => Ok(Round::Nearest),
+            _ => Err(Error::InvalidArgument {
+                option: "round",
+                value: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// Numfmt converts numbers from/to human-readable strings, the output is
+/// identical to GNU `numfmt` running in the C locale.
+///
+/// Numfmt::new().from(FromScale::Iec).to(ToScale::Si).convert("1G") -> Ok("1.1G")
+#[derive(Clone, Debug)]
+pub struct Numfmt {
+    from: FromScale,
+    to: ToScale,
+    from_unit: u64,
+    to_unit: u64,
+    round: Round,
+    suffix: Option<String>,
+    padding: isize,
+    grouping: bool,
+    thousands_separator: Option<char>,
+    format: Format,
+}
+
+#[derive(Clone, Debug, Default)]
+struct Format {
+    prefix: String,
+    suffix: String,
+    grouping: bool,
+    zero_padding: usize,
+    padding: isize,
+    precision: Option<usize>,
+}
+
+impl Default for Numfmt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Numfmt {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            from: FromScale::None,
+            to: ToScale::None,
+            from_unit: 1,
+            to_unit: 1,
+            round: Round::FromZero,
+            suffix: None,
+            padding: 0,
+            grouping: false,
+            thousands_separator: None,
+            format: Format::default(),
+        }
+    }
+
+    /// Auto-scale input numbers, `--from`.
+    #[must_use]
+    pub fn from(mut self, scale: FromScale) -> Self {
+        self.from = scale;
+        self
+    }
+
+    /// Auto-scale output numbers, `--to`.
+    #[must_use]
+    pub fn to(mut self, scale: ToScale) -> Self {
+        self.to = scale;
+        self
+    }
+
+    /// Specify the input unit size, `--from-unit`.
+    #[must_use]
+    pub fn from_unit(mut self, size: u64) -> Self {
+        self.from_unit = size.max(1);
+        self
+    }
+
+    /// Specify the output unit size, `--to-unit`.
+    #[must_use]
+    pub fn to_unit(mut self, size: u64) -> Self {
+        self.to_unit = size.max(1);
+        self
+    }
+
+    /// Use the rounding method for scaling, `--round`.
+    #[must_use]
+    pub fn round(mut self, round: Round) -> Self {
+        self.round = round;
+        self
+    }
+
+    /// Add the suffix to output numbers, and accept optional suffix in input numbers, `--suffix`.
+    #[must_use]
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Pad the output to `width` characters, positive width will right-align,
+    /// negative width will left-align, `--padding`.
+    #[must_use]
+    pub fn padding(mut self, width: isize) -> Self {
+        self.padding = width;
+        self
+    }
+
+    /// Group digits with the thousands separator, `--grouping`.
+    #[must_use]
+    pub fn grouping(mut self, grouping: bool) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Thousands separator used for grouping. The C locale has none, so
+    /// grouping has no effect unless it is set.
+    #[must_use]
+    pub fn thousands_separator(mut self, separator: char) -> Self {
+        self.thousands_separator = Some(separator);
+        self
+    }
+
+    /// Use printf style floating-point format, `--format`.
+    /// The format is `[prefix]%[0]['][-][N][.][N]f[suffix]`.
+    ///
+    /// # Errors
+    ///
+    /// Return `Error::InvalidFormat` if the format is not valid.
+    pub fn format(mut self, format: &str) -> Result<Self, Error> {
+        self.format = parse_format(format)?;
+        Ok(self)
+    }
+
+    /// convert converts a single number.
+    ///
+    /// # Errors
+    ///
+    /// Return `Error` if the input is not valid, or the value cannot be printed.
+    pub fn convert(&self, input: &str) -> Result<String, Error> {
+        if (self.grouping || self.format.grouping) && self.to != ToScale::None {
+            return Err(Error::GroupingWithScale);
+        }
+
+        let mut text = input;
+        if let Some(suffix) = &self.suffix
+            && text.len() > suffix.len()
+            && let Some(stripped) = text.strip_suffix(suffix.as_str())
+        {
+            text = stripped;
+        }
+        let text = text.trim_start_matches([' ', '\t']);
+
+        let (mut value, precision) = self.parse(text)?;
+        if self.from_unit != 1 || self.to_unit != 1 {
+            value = value
+                .mul(LongDouble::from_u64(self.from_unit))
+                .div(LongDouble::from_u64(self.to_unit));
+        }
+
+        // Can't reliably print too-large values without auto-scaling
+        let precision = self.format.precision.unwrap_or(precision);
+        let (_, digits) = value.expld(10);
+        if self.to == ToScale::None && digits as usize + precision > MAX_UNSCALED_DIGITS as usize {
+            return Err(Error::TooLargeToPrint {
+                value: value.to_g(),
+                precision,
+            });
+        }
+        if digits > MAX_ACCEPTABLE_DIGITS - 1 {
+            return Err(Error::TooLargeToHandle {
+                value: value.to_g(),
+            });
+        }
+
+        let mut buf = self.humanize(value, precision);
+        if let Some(suffix) = &self.suffix {
+            buf.push_str(suffix);
+        }
+
+        let padding = if self.format.padding != 0 {
+            self.format.padding
+        } else {
+            self.padding
+        };
+        let width = padding.unsigned_abs();
+        let len = buf.chars().count();
+        if len < width {
+            let fill = " ".repeat(width - len);
+            if padding < 0 {
+                buf.push_str(&fill);
+            } else {
+                buf.insert_str(0, &fill);
+            }
+        }
+
+        Ok(format!(
+            "{}{}{}",
+            self.format.prefix, buf, self.format.suffix
+        ))
+    }
+
+    /// parse parses the number and its optional scale suffix, and returns the
+    /// value and the number of digits after the decimal point.
+    fn parse(&self, text: &str) -> Result<(LongDouble, usize), Error> {
+        let (mut value, mut precision, remain) = parse_number(text)?;
+
+        let mut remain = remain.as_bytes();
+        if !remain.is_empty() {
+            // Skip any blanks between the number and suffix
+            while let [b' ' | b'\t', rest @ ..] = remain {
+                remain = rest;
+            }
+
+            let Some(power) = remain
+                .first()
+                .and_then(|c| SUFFIXES.iter().position(|s| s == c))
+            else {
+                return Err(Error::InvalidSuffix {
+                    input: text.to_string(),
+                });
+            };
+            remain = &remain[1..];
+
+            let base = match self.from {
+                FromScale::None => {
+                    return Err(Error::RejectingSuffix {
+                        input: text.to_string(),
+                    });
+                }
+                FromScale::Auto => {
+                    if let [b'i', rest @ ..] = remain {
+                        remain = rest;
+                        1024
+                    } else {
+                        1000
+                    }
+                }
+                FromScale::Si => 1000,
+                FromScale::Iec => 1024,
+                FromScale::IecI => {
+                    let [b'i', rest @ ..] = remain else {
+                        return Err(Error::MissingISuffix {
+                            input: text.to_string(),
+                        });
+                    };
+                    remain = rest;
+                    1024
+                }
+            };
+
+            value = value.mul(LongDouble::from_u64(base).powi(power as u32 + 1));
+            precision = 0;
+        } else if self.from == FromScale::IecI {
+            // GNU numfmt requires the suffix, even for numbers without scale
+            return Err(Error::MissingISuffix {
+                input: text.to_string(),
+            });
+        }
+
+        if !remain.is_empty() {
+            return Err(Error::TrailingSuffix {
+                input: text.to_string(),
+                suffix: String::from_utf8_lossy(remain).to_string(),
+            });
+        }
+
+        Ok((value, precision))
+    }
+
+    fn humanize(&self, value: LongDouble, precision: usize) -> String {
+        let grouping = (self.grouping || self.format.grouping)
+            .then_some(self.thousands_separator)
+            .flatten();
+
+        if self.to == ToScale::None {
+            let scale = LongDouble::from_u64(10).powi(precision as u32);
+            let value = value.mul(scale).round(self.round).div(scale);
+            return self.print(value, precision, grouping);
+        }
+
+        // Normalize value to scale
+        let base = LongDouble::from_u64(self.to.base());
+        let (value, mut power) = value.expld(self.to.base());
+
+        let adjust = match self.format.precision {
+            Some(precision) => (power * 3).min(precision as u32),
+            // for values less than 10, we allow one decimal-point digit
+            None if value.abs() < LongDouble::from_u64(10) => 1,
+            None => 0,
+        };
+        let scale = LongDouble::from_u64(10).powi(adjust);
+        let mut value = value.mul(scale).round(self.round).div(scale);
+
+        // A "999.99" can turn into 1000, so scale down
+        if value.abs() >= base {
+            value = value.div(base);
+            power += 1;
+        }
+
+        let precision = self.format.precision.unwrap_or_else(|| {
+            usize::from(!value.is_zero() && value.abs() < LongDouble::from_u64(10) && power > 0)
+        });
+
+        let mut buf = self.print(value, precision, grouping);
+        if power > 0 {
+            if power == 1 && self.to == ToScale::Si {
+                buf.push('K');
+            } else {
+                buf.push(SUFFIXES[power as usize - 1] as char);
+            }
+            if self.to == ToScale::IecI {
+                buf.push('i');
+            }
+        }
+
+        buf
+    }
+
+    /// print writes the value with `precision` digits after the decimal point,
+    /// like printf's `%.*Lf`.
+    fn print(&self, value: LongDouble, precision: usize, grouping: Option<char>) -> String {
+        let (whole, frac) = value.to_fixed(precision);
+
+        let mut number = String::with_capacity(whole.len() * 2 + frac.len() + 1);
+        for (i, c) in whole.chars().enumerate() {
+            if let Some(separator) = grouping
+                && i > 0
+                && (whole.len() - i) % 3 == 0
+            {
+                number.push(separator);
+            }
+            number.push(c);
+        }
+        if !frac.is_empty() {
+            number.push('.');
+            number.push_str(&frac);
+        }
+
+        let sign = if value.neg { "-" } else { "" };
+        let width = self.format.zero_padding.saturating_sub(sign.len());
+        format!("{}{:0>width$}", sign, number, width = width)
+    }
+}
+
+/// parse_number parses `-?[0-9]*(\.[0-9]+)?`, and returns the value, the
+/// number of digits after the decimal point and the remaining input.
+fn parse_number(text: &str) -> Result<(LongDouble, usize, &str), Error> {
+    let (neg, s) = match text.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, text),
+    };
+
+    let (mut value, s) = parse_digits(s).ok_or_else(|| Error::TooLargeToConvert {
+        input: text.to_string(),
+    })?;
+    if s.len() == text.len() - usize::from(neg) && !s.starts_with('.') {
+        return Err(Error::InvalidNumber {
+            input: text.to_string(),
+        });
+    }
+    if neg {
+        value = value.neg();
+    }
+
+    let Some(remain) = s.strip_prefix('.') else {
+        return Ok((value, 0, s));
+    };
+
+    let (frac, s) = parse_digits(remain).ok_or_else(|| Error::TooLargeToConvert {
+        input: text.to_string(),
+    })?;
+    let precision = remain.len() - s.len();
+    if precision == 0 {
+        return Err(Error::InvalidNumber {
+            input: text.to_string(),
+        });
+    }
+
+    let frac = frac.div(LongDouble::from_u64(10).powi(precision as u32));
+    value = if neg {
+        value.sub(frac)
+    } else {
+        value.add(frac)
+    };
+
+    Ok((value, precision, s))
+}
+
+/// parse_digits consumes the leading [0-9]* from s, it returns None if there
+/// are too many significant digits.
+fn parse_digits(s: &str) -> Option<(LongDouble, &str)> {
+    let ten = LongDouble::from_u64(10);
+    let mut value = LongDouble::ZERO;
+    let mut digits = 0;
+
+    let len = s.bytes().take_while(u8::is_ascii_digit).count();
+    for c in s[..len].bytes() {
+        if digits > 0 || c != b'0' {
+            digits += 1;
+        }
+        if digits > MAX_ACCEPTABLE_DIGITS {
+            return None;
+        }
+
+        value = value
+            .mul(ten)
+            .add(LongDouble::from_u64(u64::from(c - b'0')));
+    }
+
+    Some((value, &s[len..]))
+}
+
+fn parse_format(format: &str) -> Result<Format, Error> {
+    let invalid = |reason| Error::InvalidFormat {
+        format: format.to_string(),
+        reason,
+    };
+
+    let bytes = format.as_bytes();
+    let mut parsed = Format::default();
+
+    // prefix, "%%" is the escaped '%'
+    let mut i = 0;
+    let mut prefix_len = 0;
+    loop {
+        match bytes.get(i) {
+            None => return Err(invalid("format {} has no % directive")),
+            Some(b'%') if bytes.get(i + 1) != Some(&b'%') => break,
+            Some(b'%') => i += 2,
+            Some(_) => i += 1,
+        }
+        prefix_len += 1;
+    }
+    // GNU numfmt counts "%%" as one char, but copies the raw bytes
+    parsed.prefix = String::from_utf8_lossy(&bytes[..prefix_len]).to_string();
+    i += 1;
+
+    // flags
+    let mut zero = false;
+    loop {
+        let skip = bytes[i..].iter().take_while(|c| **c == b' ').count();
+        i += skip;
+        match bytes.get(i) {
+            Some(b'\'') => parsed.grouping = true,
+            Some(b'0') => zero = true,
+            _ if skip == 0 => break,
+            _ => continue,
+        }
+        i += 1;
+    }
+
+    // width, parsed like strtol
+    let (width, len) = parse_long(&bytes[i..]);
+    if len > 0 && width != 0 {
+        let width =
+            isize::try_from(width).map_err(|_| invalid("invalid format {} (width overflow)"))?;
+        if width < 0 {
+            parsed.padding = width;
+        } else if zero {
+            parsed.zero_padding = width as usize;
+        } else {
+            parsed.padding = width;
+        }
+    }
+    i += len;
+
+    if i >= bytes.len() {
+        return Err(invalid("format {} ends in %"));
+    }
+
+    if bytes[i] == b'.' {
+        i += 1;
+        if matches!(bytes.get(i), Some(b' ' | b'\t' | b'+')) {
+            return Err(invalid("invalid precision in format {}"));
+        }
+        let (precision, len) = parse_long(&bytes[i..]);
+        let precision =
+            usize::try_from(precision).map_err(|_| invalid("invalid precision in format {}"))?;
+        parsed.precision = Some(precision);
+        i += len;
+    }
+
+    if bytes.get(i) != Some(&b'f') {
+        return Err(invalid(
+            "invalid format {}, directive must be %[0]['][-][N][.][N]f",
+        ));
+    }
+    i += 1;
+
+    let suffix = &bytes[i..];
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if bytes.get(i + 1) != Some(&b'%') {
+                return Err(invalid("format {} has too many % directives"));
+            }
+            i += 1;
+        }
+        i += 1;
+    }
+    parsed.suffix = String::from_utf8_lossy(suffix).to_string();
+
+    Ok(parsed)
+}
+
+/// parse_long parses a leading integer like strtol, and returns the value and
+/// the number of consumed bytes.
+fn parse_long(s: &[u8]) -> (i64, usize) {
+    let mut i = s.iter().take_while(|c| c.is_ascii_whitespace()).count();
+    let neg = s.get(i) == Some(&b'-');
+    if let Some(b'-' | b'+') = s.get(i) {
+        i += 1;
+    }
+
+    let digits = s[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return (0, 0);
+    }
+
+    let value = s[i..i + digits].iter().fold(0i64, |acc, c| {
+        acc.saturating_mul(10).saturating_add(i64::from(c - b'0'))
+    });
+
+    (if neg { -value } else { value }, i + digits)
+}
+
+/// LongDouble emulates the x87 80-bit extended precision `long double` which
+/// GNU numfmt computes with, so the rounding errors are the same as well.
+#[derive(Clone, Copy, Debug)]
+struct LongDouble {
+    neg: bool,
+    // value is mant * 2^exp, the highest bit of mant is set unless the value is zero
+    mant: u64,
+    exp: i32,
+}
+
+impl LongDouble {
+    const ZERO: Self = Self {
+        neg: false,
+        mant: 0,
+        exp: 0,
+    };
+
+    fn from_u64(n: u64) -> Self {
+        Self::pack(false, u128::from(n), 0, false)
+    }
+
+    fn from_i64(n: i64) -> Self {
+        Self::pack(n < 0, u128::from(n.unsigned_abs()), 0, false)
+    }
+
+    /// pack rounds `wide * 2^exp` to the nearest representable value, ties to
+    /// even. `sticky` tells if there are non-zero bits below `wide`, in which
+    /// case `wide` must have more than 64 significant bits.
+    fn pack(neg: bool, wide: u128, exp: i32, sticky: bool) -> Self {
+        if wide == 0 {
+            return Self { neg, ..Self::ZERO };
+        }
+
+        let bits = 128 - wide.leading_zeros() as i32;
+        if bits <= 64 {
+            let shift = 64 - bits;
+            return Self {
+                neg,
+                mant: (wide << shift) as u64,
+                exp: exp - shift,
+            };
+        }
+
+        let shift = bits - 64;
+        let rem = wide & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let mut mant = (wide >> shift) as u64;
+        let mut exp = exp + shift;
+        if rem > half || (rem == half && (sticky || mant & 1 == 1)) {
+            mant = match mant.checked_add(1) {
+                Some(mant) => mant,
+                None => {
+                    exp += 1;
+                    1 << 63
+                }
+            };
+        }
+
+        Self { neg, mant, exp }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.mant == 0
+    }
+
+    fn neg(self) -> Self {
+        Self {
+            neg: !self.neg,
+            ..self
+        }
+    }
+
+    fn abs(self) -> Self {
+        Self { neg: false, ..self }
+    }
+
+    fn mul(self, other: Self) -> Self {
+        let neg = self.neg != other.neg;
+        let wide = u128::from(self.mant) * u128::from(other.mant);
+        Self::pack(neg, wide, self.exp + other.exp, false)
+    }
+
+    fn div(self, other: Self) -> Self {
+        let neg = self.neg != other.neg;
+        let a = u128::from(self.mant) << 64;
+        let b = u128::from(other.mant);
+        let (q, r) = (a / b, a % b);
+
+        // 62 more bits of the quotient, for rounding
+        let wide = (q << 62) | ((r << 62) / b);
+        let sticky = (r << 62) % b != 0;
+        Self::pack(neg, wide, self.exp - other.exp - 64 - 62, sticky)
+    }
+
+    fn add(self, other: Self) -> Self {
+        if other.is_zero() {
+            return Self {
+                neg: self.neg && other.neg,
+                ..self
+            };
+        }
+        if self.is_zero() {
+            return other;
+        }
+
+        let (a, b) = if self.abs() < other.abs() {
+            (other, self)
+        } else {
+            (self, other)
+        };
+
+        // Align b to a, with 62 guard bits
+        let shift = (a.exp - b.exp) as u32;
+        let aw = u128::from(a.mant) << 62;
+        let bw = u128::from(b.mant) << 62;
+        let (bw, sticky) = match bw.checked_shr(shift) {
+            Some(shifted) => (shifted, bw & ((1 << shift) - 1) != 0),
+            None => (0, true),
+        };
+
+        if a.neg == b.neg {
+            return Self::pack(a.neg, aw + bw, a.exp - 62, sticky);
+        }
+
+        let diff = aw - bw - u128::from(sticky);
+        if diff == 0 && !sticky {
+            return Self::ZERO;
+        }
+        Self::pack(a.neg, diff, a.exp - 62, sticky)
+    }
+
+    fn sub(self, other: Self) -> Self {
+        self.add(other.neg())
+    }
+
+    /// powi returns self^n, computed by repeated multiplication like GNU numfmt's `powerld`.
+    fn powi(self, n: u32) -> Self {
+        if n == 0 {
+            return Self::from_u64(1);
+        }
+
+        (1..n).fold(self, |acc, _| acc.mul(self))
+    }
+
+    /// expld divides the value by `base` until it is smaller than `base`, and
+    /// returns the result and the number of divisions.
+    fn expld(self, base: u64) -> (Self, u32) {
+        let base = Self::from_u64(base);
+        let mut value = self;
+        let mut power = 0;
+        while value.abs() >= base {
+            value = value.div(base);
+            power += 1;
+        }
+
+        (value, power)
+    }
+
+    /// trunc converts the value to an integer, rounding toward zero like a C cast.
+    fn trunc(self) -> i64 {
+        if self.is_zero() || self.exp <= -64 {
+            return 0;
+        }
+
+        let magnitude = if self.exp < 0 {
+            self.mant >> -self.exp
+        } else {
+            match self.mant.checked_shl(self.exp as u32) {
+                Some(magnitude) if magnitude >> self.exp == self.mant => magnitude,
+                // out of range, x87 returns the "integer indefinite"
+                _ => return i64::MIN,
+            }
+        };
+
+        match i64::try_from(magnitude) {
+            Ok(v) if self.neg => -v,
+            Ok(v) => v,
+            Err(_) => i64::MIN,
+        }
+    }
+
+    /// round rounds the value to an integer, the same as GNU numfmt's `simple_round`.
+    fn round(self, mode: Round) -> Self {
+        let ceiling = |value: Self| {
+            let int = value.trunc();
+            if Self::from_i64(int) < value {
+                int.wrapping_add(1)
+            } else {
+                int
+            }
+        };
+        let floor = |value: Self| ceiling(value.neg()).wrapping_neg();
+        let neg = self < Self::ZERO;
+
+        let intmax = Self::from_i64(i64::MAX);
+        let intmax_mul = Self::from_i64(self.div(intmax).trunc());
+        let value = self.sub(intmax.mul(intmax_mul));
+
+        let rounded = match mode {
+            Round::Up => ceiling(value),
+            Round::Down => floor(value),
+            Round::FromZero if neg => floor(value),
+            Round::FromZero => ceiling(value),
+            Round::TowardsZero => value.trunc(),
+            Round::Nearest => {
+                let half = Self::pack(false, 1, -1, false);
+                if neg {
+                    value.sub(half).trunc()
+                } else {
+                    value.add(half).trunc()
+                }
+            }
+        };
+
+        intmax.mul(intmax_mul).add(Self::from_i64(rounded))
+    }
+
+    /// to_fixed returns the integer and fraction digits of the value, rounded
+    /// to `precision` digits after the decimal point, ties to even.
+    fn to_fixed(self, precision: usize) -> (String, String) {
+        let mut digits = Vec::with_capacity(precision + 32);
+
+        // fraction bits, and the number of them
+        let (frac, bits) = if self.exp >= 0 {
+            let int = u128::from(self.mant) << self.exp;
+            digits.extend(int.to_string().bytes());
+            (0, 0)
+        } else {
+            let bits = -self.exp as u32;
+            let int = self.mant.checked_shr(bits).unwrap_or_default();
+            digits.extend(int.to_string().bytes());
+            // Anything that small is printed as zero anyway
+            let frac = u128::from(self.mant) & 1u128.checked_shl(bits).map_or(u128::MAX, |v| v - 1);
+            if bits > 124 { (0, 0) } else { (frac, bits) }
+        };
+
+        let int_len = digits.len();
+        let mut frac = frac;
+        for _ in 0..precision {
+            frac *= 10;
+            digits.push(b'0' + (frac >> bits) as u8);
+            frac &= (1 << bits) - 1;
+        }
+
+        let round_up = bits > 0 && {
+            let half = 1u128 << (bits - 1);
+            frac > half || (frac == half && digits.last().is_some_and(|d| d % 2 == 1))
+        };
+        let mut int_len = int_len;
+        if round_up {
+            let mut carry = true;
+            for d in digits.iter_mut().rev() {
+                if *d == b'9' {
+                    *d = b'0';
+                } else {
+                    *d += 1;
+                    carry = false;
+                    break;
+                }
+            }
+            if carry {
+                digits.insert(0, b'1');
+                int_len += 1;
+            }
+        }
+
+        let (int, frac) = digits.split_at(int_len);
+        (
+            String::from_utf8_lossy(int).to_string(),
+            String::from_utf8_lossy(frac).to_string(),
+        )
+    }
+
+    /// to_g formats the value like printf's `%Lg`
+    fn to_g(self) -> String {
+        let magnitude = self.mant as f64 * 2f64.powi(self.exp);
+        let value = if self.neg { -magnitude } else { magnitude };
+        let exp = if value == 0.0 {
+            0
+        } else {
+            value.abs().log10().floor() as i32
+        };
+
+        if (-4..6).contains(&exp) {
+            let precision = (5 - exp).max(0) as usize;
+            let s = format!("{:.*}", precision, value);
+            if s.contains('.') {
+                s.trim_end_matches('0').trim_end_matches('.').to_string()
+            } else {
+                s
+            }
+        } else {
+            let s = format!("{:.5e}", value);
+            let (mantissa, exp) = s.split_once('e').unwrap_or((&s, "0"));
+            let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+            let exp = exp.parse::<i32>().unwrap_or_default();
+            let sign = if exp < 0 { '-' } else { '+' };
+            format!("{}e{}{:02}", mantissa, sign, exp.abs())
+        }
+    }
+}
+
+impl PartialEq for LongDouble {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for LongDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        let magnitude = |v: &Self| (!v.is_zero(), v.exp, v.mant);
+
+        let ordering = match (self.neg && !self.is_zero(), other.neg && !other.is_zero()) {
+            (false, false) => magnitude(self).cmp(&magnitude(other)),
+            (true, true) => magnitude(other).cmp(&magnitude(self)),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        };
+
+        Some(ordering)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The expected outputs are generated by GNU coreutils numfmt 9.1
+    #[test]
+    fn golden() {
+        let si = Numfmt::new().to(ToScale::Si);
+        let iec = Numfmt::new().to(ToScale::Iec);
+        let iec_i = Numfmt::new().to(ToScale::IecI);
+
+        let tests = [
+            (&si, "4.5", "4"),
+            (&si, "5.5", "6"),
+            (&si, "5.55", "6"),
+            (&si, "1150", "1.2K"),
+            (&si, "999999", "1.0M"),
+            (&si, "9999", "10K"),
+            (&si, "99999", "100K"),
+            (&si, "-1500", "-1.5K"),
+            (&si, "999.95", "1.0K"),
+            (&si, "0", "0"),
+            (&si, "0.4", "0"),
+            (&si, "-0.4", "-0"),
+            (&si, "0.001", "0"),
+            (&si, "10.5", "11"),
+            (&si, "9.96", "10"),
+            (&si, "99.5", "100"),
+            (&si, "123456789012345678901234567", "124Y"),
+            (&si, "12345678901234567890123456", "13Y"),
+            (&iec, "1023.95", "1.0K"),
+            (&iec, "1048575", "1.0M"),
+            (&iec, "1180591620717411303424", "1.0Z"),
+            (&iec_i, "1", "1"),
+            (&iec_i, "1024", "1.0Ki"),
+        ];
+
+        for (numfmt, input, want) in tests {
+            assert_eq!(numfmt.convert(input).unwrap(), want, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn unscaled() {
+        let numfmt = Numfmt::new();
+        let tests = [
+            ("12", "12"),
+            (" 12", "12"),
+            ("1.50", "1.50"),
+            ("-1.5", "-1.5"),
+            (".5", "0.5"),
+            ("0.000", "0.000"),
+            ("12345678901234567", "12345678901234567"),
+            ("1234567890123456789", "1234567890123456789"),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(numfmt.convert(input).unwrap(), want, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn from() {
+        let tests = [
+            (FromScale::Si, "1.5K", "1500"),
+            (FromScale::Iec, "1.001K", "1026"),
+            (FromScale::Auto, "1Ki", "1024"),
+            (FromScale::Auto, "1K", "1000"),
+            (FromScale::Auto, "1.5Mi", "1572864"),
+            (FromScale::Auto, "1.5 Mi", "1572864"),
+            (FromScale::IecI, "1Ki", "1024"),
+        ];
+
+        for (from, input, want) in tests {
+            let got = Numfmt::new().from(from).convert(input).unwrap();
+            assert_eq!(got, want, "from: {from:?}, input: {input}");
+        }
+
+        let numfmt = Numfmt::new().from(FromScale::Iec).to(ToScale::Si);
+        assert_eq!(numfmt.convert("1G").unwrap(), "1.1G");
+        let numfmt = Numfmt::new().from(FromScale::Auto).to(ToScale::IecI);
+        assert_eq!(numfmt.convert("1.5G").unwrap(), "1.4Gi");
+    }
+
+    #[test]
+    fn round() {
+        let tests = [
+            (Round::Up, "1001", "1.1K"),
+            (Round::Down, "1999", "1.9K"),
+            (Round::TowardsZero, "-1999", "-1.9K"),
+            (Round::Down, "-1001", "-1.1K"),
+            (Round::Nearest, "1150", "1.2K"),
+            (Round::Nearest, "1250", "1.3K"),
+        ];
+
+        for (round, input, want) in tests {
+            let got = Numfmt::new()
+                .to(ToScale::Si)
+                .round(round)
+                .convert(input)
+                .unwrap();
+            assert_eq!(got, want, "round: {round:?}, input: {input}");
+        }
+
+        let numfmt = Numfmt::new().round(Round::Nearest);
+        assert_eq!(numfmt.convert("-2.5").unwrap(), "-2.5");
+        let numfmt = Numfmt::new().round(Round::Nearest).to_unit(3);
+        assert_eq!(numfmt.convert("10").unwrap(), "3");
+
+        // long double can't represent these exactly, same as GNU numfmt
+        let numfmt = Numfmt::new().format("%.2f").unwrap();
+        assert_eq!(numfmt.convert("0.3").unwrap(), "0.31");
+        let numfmt = Numfmt::new().round(Round::Down).format("%.5f").unwrap();
+        assert_eq!(numfmt.convert("2.59").unwrap(), "2.58999");
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(Numfmt::new().from_unit(512).convert("4").unwrap(), "2048");
+        assert_eq!(Numfmt::new().to_unit(3).convert("10").unwrap(), "4");
+
+        let numfmt = Numfmt::new().to_unit(3).format("%.3f").unwrap();
+        assert_eq!(numfmt.convert("10").unwrap(), "3.334");
+    }
+
+    #[test]
+    fn suffix_and_padding() {
+        let tests = [
+            (Numfmt::new().suffix("B").to(ToScale::Si), "1500", "1.5KB"),
+            (
+                Numfmt::new().suffix("B").from(FromScale::Si),
+                "1.5KB",
+                "1500B",
+            ),
+            (Numfmt::new().suffix("B"), "12B", "12B"),
+            (Numfmt::new().suffix("B"), "12", "12B"),
+            (
+                Numfmt::new().suffix("iB").from(FromScale::Si),
+                "1KiB",
+                "1000iB",
+            ),
+            (
+                Numfmt::new().suffix("B").padding(8).to(ToScale::Si),
+                "1500",
+                "   1.5KB",
+            ),
+            (Numfmt::new().padding(8), "12", "      12"),
+            (Numfmt::new().padding(-8), "12", "12      "),
+        ];
+
+        for (numfmt, input, want) in tests {
+            assert_eq!(numfmt.convert(input).unwrap(), want, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn format() {
+        let tests = [
+            ("%10f", Numfmt::new(), "12", "        12"),
+            ("%-10f|", Numfmt::new(), "12", "12        |"),
+            ("%010f", Numfmt::new(), "-12", "-000000012"),
+            ("abc%.3fxyz", Numfmt::new(), "1.5", "abc1.500xyz"),
+            ("%.2f", Numfmt::new().to(ToScale::Si), "5.555", "6.00"),
+            ("%.2f", Numfmt::new().to(ToScale::Si), "1234567", "1.24M"),
+            ("%.3f", Numfmt::new().to(ToScale::Si), "1234567", "1.235M"),
+            ("%.0f", Numfmt::new().to(ToScale::Si), "1500", "2K"),
+            ("%.1f", Numfmt::new().to(ToScale::Si), "999.95", "1.0K"),
+            ("%.3f", Numfmt::new().to(ToScale::Si), "0.05", "1.000"),
+            ("%.2f", Numfmt::new().to(ToScale::Si), "1", "1.00"),
+            ("%.1f", Numfmt::new().to(ToScale::Iec), "500", "500.0"),
+            (
+                "%.5f",
+                Numfmt::new().from(FromScale::Iec).to(ToScale::Iec),
+                "1.5K",
+                "1.50000K",
+            ),
+            (
+                "%.2f",
+                Numfmt::new().to(ToScale::IecI).suffix("B"),
+                "1536",
+                "1.50KiB",
+            ),
+            (
+                "%08f",
+                Numfmt::new().to(ToScale::Si).suffix("B"),
+                "1500",
+                "000001.5KB",
+            ),
+            (
+                "%08.2f",
+                Numfmt::new().to(ToScale::Si),
+                "-1500",
+                "-0001.50K",
+            ),
+            ("%-08f|", Numfmt::new(), "15", "15      |"),
+            ("%0-5f", Numfmt::new(), "1", "1    "),
+            ("%%%f%%", Numfmt::new(), "15", "%15%%"),
+            ("ab%%cd%f", Numfmt::new(), "1", "ab%%c1"),
+            ("%5.f", Numfmt::new(), "15.5", "   16"),
+            ("% f", Numfmt::new(), "15", "15"),
+            ("%  5f|", Numfmt::new(), "1", "    1|"),
+            ("%+5f|", Numfmt::new(), "1", "    1|"),
+            ("%'08f", Numfmt::new(), "15", "00000015"),
+            ("%'f", Numfmt::new(), "1234567", "1234567"),
+            (
+                "%'f",
+                Numfmt::new().thousands_separator(','),
+                "1234567",
+                "1,234,567",
+            ),
+            ("%10f|", Numfmt::new().padding(5), "1", "         1|"),
+            ("%05f|", Numfmt::new().padding(8), "1", "   00001|"),
+            ("%.2f", Numfmt::new(), "1.555", "1.56"),
+            ("%.2f", Numfmt::new().round(Round::Down), "1.559", "1.55"),
+            ("%.1f", Numfmt::new(), "1.55", "1.6"),
+            ("%.1f", Numfmt::new(), "1.45", "1.5"),
+            (
+                "%.1f",
+                Numfmt::new(),
+                "123456789012345678",
+                "123456789012345678.0",
+            ),
+        ];
+
+        for (format, numfmt, input, want) in tests {
+            let numfmt = numfmt.format(format).unwrap();
+            assert_eq!(
+                numfmt.convert(input).unwrap(),
+                want,
+                "format: {format}, input: {input}"
+            );
+        }
+
+        let tests = [
+            (
+                "%d",
+                "invalid format '%d', directive must be %[0]['][-][N][.][N]f",
+            ),
+            ("%f %f", "format '%f %f' has too many % directives"),
+            (
+                "%- 5f",
+                "invalid format '%- 5f', directive must be %[0]['][-][N][.][N]f",
+            ),
+            ("%", "format '%' ends in %"),
+            ("%.-1f", "invalid precision in format '%.-1f'"),
+            ("x", "format 'x' has no % directive"),
+        ];
+
+        for (format, want) in tests {
+            let err = Numfmt::new().format(format).unwrap_err();
+            assert_eq!(err.to_string(), want, "format: {format}");
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let tests = [
+            (
+                Numfmt::new().from(FromScale::Si),
+                "1k",
+                "invalid suffix in input: '1k'",
+            ),
+            (
+                Numfmt::new().from(FromScale::Iec),
+                "1Ki",
+                "invalid suffix in input '1Ki': 'i'",
+            ),
+            (
+                Numfmt::new().from(FromScale::IecI),
+                "1K",
+                "missing 'i' suffix in input: '1K' (e.g Ki/Mi/Gi)",
+            ),
+            (
+                Numfmt::new(),
+                "1K",
+                "rejecting suffix in input: '1K' (consider using --from)",
+            ),
+            (
+                Numfmt::new().from(FromScale::IecI),
+                "5",
+                "missing 'i' suffix in input: '5' (e.g Ki/Mi/Gi)",
+            ),
+            (
+                Numfmt::new().grouping(true).to(ToScale::Si),
+                "5",
+                "grouping cannot be combined with --to",
+            ),
+            (Numfmt::new(), "+1.5", "invalid number: '+1.5'"),
+            (Numfmt::new(), "1.", "invalid number: '1.'"),
+            (Numfmt::new(), "abc", "invalid number: 'abc'"),
+            (Numfmt::new(), "1x", "invalid suffix in input: '1x'"),
+            (
+                Numfmt::new().to(ToScale::Si),
+                "1e3",
+                "invalid suffix in input: '1e3'",
+            ),
+            (
+                Numfmt::new().to(ToScale::Si),
+                "1234567890123456789012345678",
+                "value too large to be converted: '1234567890123456789012345678'",
+            ),
+            (
+                Numfmt::new(),
+                "1234567890123456789.5",
+                "value/precision too large to be printed: '1.23457e+18/1' (consider using --to)",
+            ),
+            (
+                Numfmt::new(),
+                "12345678901234567890",
+                "value too large to be printed: '1.23457e+19' (consider using --to)",
+            ),
+            (
+                Numfmt::new().from(FromScale::Si).to(ToScale::Si),
+                "1000Y",
+                "value too large to be printed: '1e+27' (cannot handle values > 999Y)",
+            ),
+        ];
+
+        for (numfmt, input, want) in tests {
+            let err = numfmt.convert(input).unwrap_err();
+            assert_eq!(err.to_string(), want, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn options_from_str() {
+        assert_eq!("iec-i".parse::<FromScale>().unwrap(), FromScale::IecI);
+        assert_eq!("auto".parse::<FromScale>().unwrap(), FromScale::Auto);
+        assert_eq!("si".parse::<ToScale>().unwrap(), ToScale::Si);
+        assert_eq!("nearest".parse::<Round>().unwrap(), Round::Nearest);
+        assert_eq!(
+            "auto".parse::<ToScale>().unwrap_err().to_string(),
+            "invalid argument 'auto' for '--to'"
+        );
+    }
+}
@@ -0,0 +1,30 @@
+// Helpers shared by the clap value parsers of the bytes and duration modules
+
+use std::ffi::OsStr;
+
+use clap::error::ErrorKind;
+use clap::{Arg, Command, Error};
+
+/// to_str returns the value as UTF-8, or clap's invalid UTF-8 error.
+pub(crate) fn to_str<'a>(cmd: &Command, value: &'a OsStr) -> Result<&'a str, Error> {
+    value
+        .to_str()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))
+}
+
+/// invalid_value returns the error for a value which failed to parse, with a
+/// tip on the accepted syntax.
+pub(crate) fn invalid_value(
+    cmd: &Command,
+    arg: Option<&Arg>,
+    value: &str,
+    err: impl std::fmt::Display,
+    hint: &str,
+) -> Error {
+    let arg = arg.map_or_else(|| "...".to_string(), |arg| arg.to_string());
+    let message = format!(
+        "invalid value '{}' for '{}': {}\n\n  tip: {}\n",
+        value, arg, err, hint
+    );
+    Error::raw(ErrorKind::ValueValidation, message).with_cmd(cmd)
+}
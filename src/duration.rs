@@ -12,12 +12,18 @@ const HOUR: i64 = 60 * MINUTE;
 const DAY: i64 = 24 * HOUR;
 const WEEK: i64 = 7 * DAY;
 
+// Largest `Duration`, in nanoseconds
+const MAX_NANOS: u128 = u64::MAX as u128 * SECOND as u128 + (SECOND - 1) as u128;
+
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[non_exhaustive]
 pub enum Error {
     BadInteger,
     InvalidDuration,
     MissingUnit,
     UnknownUnit,
+    OutOfBounds(OutOfBounds),
+    InvalidRange,
 }
 
 impl std::error::Error for Error {}
@@ -29,84 +35,578 @@ impl Display for Error {
             Error::InvalidDuration => "invalid duration",
             Error::MissingUnit => "missing unit",
             Error::UnknownUnit => "unknown unit",
+            Error::InvalidRange => "invalid range",
+            Error::OutOfBounds(err) => return write!(f, "{}", err),
         };
 
         write!(f, "{}", msg)
     }
 }
 
-/// leading_int consumes the leading [0-9]* from s
-fn leading_int(s: &[u8]) -> Result<(u64, &[u8]), Error> {
-    let mut consumed = 0;
-    let o = s
-        .iter()
-        .take_while(|c| **c >= b'0' && **c <= b'9')
-        .try_fold(0u64, |x, &c| {
-            consumed += 1;
-
-            if x > u64::MAX / 10 {
-                None
-            } else {
-                Some(10 * x + c as u64 - b'0' as u64)
-            }
-        });
+impl From<OutOfBounds> for Error {
+    fn from(err: OutOfBounds) -> Self {
+        Self::OutOfBounds(err)
+    }
+}
 
-    match o {
-        Some(v) => Ok((v, &s[consumed..])),
-        None => Err(Error::BadInteger),
+/// leading_int consumes the leading [0-9]* from s
+const fn leading_int(s: &[u8]) -> Result<(u64, &[u8]), Error> {
+    let mut x = 0u64;
+    let mut i = 0;
+    while i < s.len() && s[i].is_ascii_digit() {
+        x = match x.checked_mul(10) {
+            Some(x) => match x.checked_add((s[i] - b'0') as u64) {
+                Some(x) => x,
+                None => return Err(Error::BadInteger),
+            },
+            None => return Err(Error::BadInteger),
+        };
+        i += 1;
     }
+
+    Ok((x, s.split_at(i).1))
 }
 
 /// leading_fraction consumes the leader [0-9]* from s.
 /// It is used only for fractions, so does not return an error on overflow,
 /// it just stops accumulating precision.
-fn leading_fraction(s: &[u8]) -> (i64, f64, &[u8]) {
-    let mut consumed = 0;
+const fn leading_fraction(s: &[u8]) -> (i64, f64, &[u8]) {
+    let mut x = 0i64;
     let mut scale = 1.0;
     let mut overflow = false;
+    let mut i = 0;
 
-    let o = s
-        .iter()
-        .take_while(|c| **c >= b'0' && **c <= b'9')
-        .try_fold(0, |x, &c| {
-            consumed += 1;
-
-            if overflow {
-                return Some(x);
-            }
+    while i < s.len() && s[i].is_ascii_digit() {
+        i += 1;
+        if overflow {
+            continue;
+        }
 
-            if x > i64::MAX / 10 {
-                overflow = true;
-                return Some(x);
-            }
+        if x > i64::MAX / 10 {
+            overflow = true;
+            continue;
+        }
 
-            let y = x * 10 + c as i64 - b'0' as i64;
-            if y < 0 {
-                overflow = true;
-                return Some(x);
-            }
+        let y = x * 10 + (s[i - 1] - b'0') as i64;
+        if y < 0 {
+            overflow = true;
+            continue;
+        }
 
-            scale *= 10.0;
-            Some(y)
-        })
-        .unwrap();
+        x = y;
+        scale *= 10.0;
+    }
 
-    (o, scale, &s[consumed..])
+    (x, scale, s.split_at(i).1)
 }
 
 /// parse_duration parses a duration string.
 /// A duration string is a possibly signed sequence of decimal numbers,
 /// each with optional fraction and a unit suffix, such as "300ms", "-1.5h" or "2h45m".
 /// Valid time units are "ns", "us" (or "µs"), "ms", "s", "m", "h", "d", "w".
-pub fn parse_duration(text: &str) -> Result<Duration, Error> {
-    let d = parse(text)?;
+/// Any `Duration` can be parsed, negative durations are rejected.
+/// Text with a ':' is parsed as a clock with `parse_clock`, e.g. "1:30:00".
+///
+/// It is a const fn, see also `duration!`.
+pub const fn parse_duration(text: &str) -> Result<Duration, Error> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b':' {
+            return parse_clock(text);
+        }
+        i += 1;
+    }
+
+    let d = match parse_nanos(text) {
+        Ok((false, d)) => d,
+        Ok((true, _)) => return Err(Error::InvalidDuration),
+        Err(err) => return Err(err),
+    };
+
+    Ok(Duration::new(
+        (d / SECOND as u128) as u64,
+        (d % SECOND as u128) as u32,
+    ))
+}
+
+/// parse_clock parses a clock duration: "MM:SS", "HH:MM:SS", "D:HH:MM:SS",
+/// "D-HH:MM:SS" like Slurm, or "Dd HH:MM:SS". The seconds may have a fraction,
+/// e.g. "01:02:03.450". The leading field may exceed its usual range, "90:00"
+/// is 90 minutes, the others must not.
+///
+/// parse_clock("1:30:00") -> Ok(5400s)
+///
+/// It is a const fn.
+pub const fn parse_clock(text: &str) -> Result<Duration, Error> {
+    let mut s = text.trim_ascii().as_bytes();
+
+    // Consume the days of "D-" or "Dd "
+    let mut days = 0;
+    let mut has_days = false;
+    let (n, rest) = match leading_int(s) {
+        Ok(v) => v,
+        Err(err) => return Err(err),
+    };
+    if rest.len() < s.len() && !rest.is_empty() && (rest[0] == b'-' || rest[0] == b'd') {
+        let mut spaces = 0;
+        if rest[0] == b'd' {
+            while spaces + 1 < rest.len() && rest[spaces + 1] == b' ' {
+                spaces += 1;
+            }
+            if spaces == 0 {
+                return Err(Error::InvalidDuration);
+            }
+        }
+        days = n;
+        has_days = true;
+        s = rest.split_at(1 + spaces).1;
+    }
+
+    // Consume fields [0-9]+(:[0-9]{1,2})*
+    let mut fields = [0u64; 4];
+    let mut count = 0;
+    loop {
+        let (n, rest) = match leading_int(s) {
+            Ok(v) => v,
+            Err(err) => return Err(err),
+        };
+        let digits = s.len() - rest.len();
+        if digits == 0 || ((count > 0 || has_days) && digits > 2) || count == fields.len() {
+            return Err(Error::InvalidDuration);
+        }
+        fields[count] = n;
+        count += 1;
+        s = rest;
+        if s.is_empty() || s[0] != b':' {
+            break;
+        }
+        s = s.split_at(1).1;
+    }
+
+    // Consume the fraction of seconds \.[0-9]*
+    let mut nanos = 0u128;
+    if !s.is_empty() && s[0] == b'.' {
+        let mut i = 1;
+        let mut scale = SECOND as u128;
+        while i < s.len() && s[i].is_ascii_digit() {
+            scale /= 10;
+            nanos += (s[i] - b'0') as u128 * scale;
+            i += 1;
+        }
+        s = s.split_at(i).1;
+    }
+    if !s.is_empty() {
+        return Err(Error::InvalidDuration);
+    }
+
+    let (hours, minutes, seconds) = match (count, has_days) {
+        (2, false) => (0, fields[0], fields[1]),
+        (3, false) => (fields[0], fields[1], fields[2]),
+        (3, true) => (fields[0], fields[1], fields[2]),
+        (4, false) => {
+            days = fields[0];
+            has_days = true;
+            (fields[1], fields[2], fields[3])
+        }
+        _ => return Err(Error::InvalidDuration),
+    };
+    if seconds >= 60 || (count > 2 && minutes >= 60) || (has_days && hours >= 24) {
+        return Err(Error::InvalidDuration);
+    }
+
+    let minutes = (days as u128 * 24 + hours as u128) * 60 + minutes as u128;
+    let d = (minutes * 60 + seconds as u128) * SECOND as u128 + nanos;
+    if d > MAX_NANOS {
+        return Err(Error::InvalidDuration);
+    }
+
+    Ok(Duration::new(
+        (d / SECOND as u128) as u64,
+        (d % SECOND as u128) as u32,
+    ))
+}
+
+/// duration! parses a duration at compile time, invalid durations fail
+/// compilation.
+///
+/// const TIMEOUT: Duration = duration!("1h30m"); -> 5400s
+#[macro_export]
+macro_rules! duration {
+    ($text:expr) => {{
+        const DURATION: ::std::time::Duration = match $crate::duration::parse_duration($text) {
+            Ok(d) => d,
+            Err(_) => panic!(concat!("invalid duration ", stringify!($text))),
+        };
+        DURATION
+    }};
+}
+
+/// from_env parses the environment variable `key` with `parse_duration`.
+///
+/// from_env("APP_TIMEOUT") -> Ok(Duration::from_secs(30)) with APP_TIMEOUT=30s
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is unset or not valid.
+#[cfg(feature = "env")]
+pub fn from_env(key: &str) -> Result<Duration, crate::env::Error> {
+    from_source(&crate::env::Env, key)
+}
+
+/// from_env_or is like `from_env`, but returns `default` if the variable is
+/// unset or empty.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is not valid.
+#[cfg(feature = "env")]
+pub fn from_env_or(key: &str, default: Duration) -> Result<Duration, crate::env::Error> {
+    from_source_or(&crate::env::Env, key, default)
+}
+
+/// from_source is `from_env` reading from any `env::Source`.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is unset or not valid.
+#[cfg(feature = "env")]
+pub fn from_source<S>(source: &S, key: &str) -> Result<Duration, crate::env::Error>
+where
+    S: crate::env::Source + ?Sized,
+{
+    crate::env::require(source, key, parse_duration)
+}
+
+/// from_source_or is `from_env_or` reading from any `env::Source`.
+///
+/// # Errors
+///
+/// Return `env::Error` naming the variable if it is not valid.
+#[cfg(feature = "env")]
+pub fn from_source_or<S>(
+    source: &S,
+    key: &str,
+    default: Duration,
+) -> Result<Duration, crate::env::Error>
+where
+    S: crate::env::Source + ?Sized,
+{
+    Ok(crate::env::parse(source, key, parse_duration)?.unwrap_or(default))
+}
+
+/// Bounds restricts durations to an inclusive range, and optionally to
+/// multiples of a duration, e.g. whole seconds.
+///
+/// Bounds::new().min(Duration::from_secs(1)).parse("500ms") -> Err(must be at least 1s, got 500ms)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Bounds {
+    min: Duration,
+    max: Duration,
+    multiple_of: Duration,
+}
 
-    Ok(Duration::from_nanos(d as u64))
+impl Default for Bounds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bounds {
+    /// new returns bounds which accept any duration.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            min: Duration::ZERO,
+            max: Duration::MAX,
+            multiple_of: Duration::ZERO,
+        }
+    }
+
+    /// min sets the shortest accepted duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `min` is longer than the longest accepted duration.
+    #[must_use]
+    pub const fn min(mut self, min: Duration) -> Self {
+        assert!(
+            min.as_nanos() <= self.max.as_nanos(),
+            "bounds min is greater than max"
+        );
+        self.min = min;
+        self
+    }
+
+    /// max sets the longest accepted duration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is shorter than the shortest accepted duration.
+    #[must_use]
+    pub const fn max(mut self, max: Duration) -> Self {
+        assert!(
+            self.min.as_nanos() <= max.as_nanos(),
+            "bounds min is greater than max"
+        );
+        self.max = max;
+        self
+    }
+
+    /// multiple_of only accepts multiples of `d`, zero accepts any duration.
+    #[must_use]
+    pub const fn multiple_of(mut self, d: Duration) -> Self {
+        self.multiple_of = d;
+        self
+    }
+
+    /// check returns `d` if it is within the bounds.
+    ///
+    /// # Errors
+    ///
+    /// Return `OutOfBounds` if the duration is not within the bounds.
+    pub fn check(&self, d: Duration) -> Result<Duration, OutOfBounds> {
+        if d < self.min {
+            Err(OutOfBounds::TooSmall {
+                min: self.min,
+                got: d,
+            })
+        } else if d > self.max {
+            Err(OutOfBounds::TooLarge {
+                max: self.max,
+                got: d,
+            })
+        } else if !self.multiple_of.is_zero()
+            && !d.as_nanos().is_multiple_of(self.multiple_of.as_nanos())
+        {
+            Err(OutOfBounds::NotMultiple {
+                multiple_of: self.multiple_of,
+                got: d,
+            })
+        } else {
+            Ok(d)
+        }
+    }
+
+    /// parse parses a duration with `parse_duration` and checks it is within
+    /// the bounds.
+    ///
+    /// # Errors
+    ///
+    /// Return `Error` if the input is not valid, or `Error::OutOfBounds` if the
+    /// duration is not within the bounds.
+    pub fn parse(&self, text: &str) -> Result<Duration, Error> {
+        Ok(self.check(parse_duration(text)?)?)
+    }
+}
+
+/// OutOfBounds is a duration rejected by `Bounds`, it formats the durations
+/// with `duration`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum OutOfBounds {
+    TooSmall {
+        min: Duration,
+        got: Duration,
+    },
+    TooLarge {
+        max: Duration,
+        got: Duration,
+    },
+    NotMultiple {
+        multiple_of: Duration,
+        got: Duration,
+    },
+}
+
+impl std::error::Error for OutOfBounds {}
+
+impl Display for OutOfBounds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutOfBounds::TooSmall { min, got } => {
+                write!(
+                    f,
+                    "must be at least {}, got {}",
+                    duration(min),
+                    duration(got)
+                )
+            }
+            OutOfBounds::TooLarge { max, got } => {
+                write!(
+                    f,
+                    "must be at most {}, got {}",
+                    duration(max),
+                    duration(got)
+                )
+            }
+            OutOfBounds::NotMultiple { multiple_of, got } => write!(
+                f,
+                "must be a multiple of {}, got {}",
+                duration(multiple_of),
+                duration(got)
+            ),
+        }
+    }
+}
+
+/// DurationRange is a bounded or jittered duration, e.g. a retry delay.
+///
+/// See also: `parse_duration_range`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DurationRange {
+    /// Between is any duration from the first to the second, "1s..=5s".
+    Between(Duration, Duration),
+    /// Exclusive is any duration from the first up to, but not including,
+    /// the second, "1s..5s".
+    Exclusive(Duration, Duration),
+    /// Jitter is a duration plus or minus another one, "30s±5s".
+    Jitter(Duration, Duration),
+    /// Percent is a duration plus or minus a percentage of it, "30s±10%".
+    Percent(Duration, f64),
+}
+
+impl DurationRange {
+    /// min returns the shortest duration in the range, jitter stops at zero.
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        match *self {
+            DurationRange::Between(min, _) | DurationRange::Exclusive(min, _) => min,
+            DurationRange::Jitter(d, _) | DurationRange::Percent(d, _) => {
+                d.saturating_sub(self.jitter())
+            }
+        }
+    }
+
+    /// max returns the longest duration in the range.
+    #[must_use]
+    pub fn max(&self) -> Duration {
+        match *self {
+            DurationRange::Between(_, max) => max,
+            DurationRange::Exclusive(min, end) => {
+                end.saturating_sub(Duration::from_nanos(1)).max(min)
+            }
+            DurationRange::Jitter(d, _) | DurationRange::Percent(d, _) => {
+                d.saturating_add(self.jitter())
+            }
+        }
+    }
+
+    fn jitter(&self) -> Duration {
+        match *self {
+            DurationRange::Between(..) | DurationRange::Exclusive(..) => Duration::ZERO,
+            DurationRange::Jitter(_, jitter) => jitter,
+            DurationRange::Percent(d, percent) => {
+                // A directly built percent may be invalid, negative and NaN are
+                // no jitter, and large ones saturate
+                let secs = d.as_secs_f64() * (percent / 100.0);
+                match Duration::try_from_secs_f64(secs) {
+                    Ok(jitter) => jitter,
+                    Err(_) if secs > 0.0 => Duration::MAX,
+                    Err(_) => Duration::ZERO,
+                }
+            }
+        }
+    }
+
+    /// contains reports whether `d` is within the range.
+    #[must_use]
+    pub fn contains(&self, d: Duration) -> bool {
+        match *self {
+            DurationRange::Exclusive(min, end) => (min..end).contains(&d),
+            _ => (self.min()..=self.max()).contains(&d),
+        }
+    }
+
+    /// sample maps `r`, a uniform random number in [0, 1] from the caller's
+    /// RNG, to a duration in the range. 0 is the shortest and 1 the longest.
+    ///
+    /// parse_duration_range("30s±10%")?.sample(0.5) -> 30s
+    #[must_use]
+    pub fn sample(&self, r: f64) -> Duration {
+        let (min, max) = (self.min(), self.max());
+        let r = if r.is_nan() { 0.0 } else { r.clamp(0.0, 1.0) };
+
+        // Interpolate in nanoseconds, the span may not fit a Duration via f64
+        let span = max.saturating_sub(min).as_nanos();
+        let d = ((span as f64 * r) as u128).min(span);
+        let d = Duration::new((d / SECOND as u128) as u64, (d % SECOND as u128) as u32);
+        min.saturating_add(d).min(max)
+    }
+}
+
+/// Formats the range in the syntax of `parse_duration_range`, with durations
+/// from `duration`.
+impl Display for DurationRange {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationRange::Between(min, max) => write!(f, "{}..={}", duration(min), duration(max)),
+            DurationRange::Exclusive(min, end) => write!(f, "{}..{}", duration(min), duration(end)),
+            DurationRange::Jitter(d, jitter) if jitter.is_zero() => write!(f, "{}", duration(d)),
+            DurationRange::Jitter(d, jitter) => write!(f, "{}±{}", duration(d), duration(jitter)),
+            DurationRange::Percent(d, percent) => write!(f, "{}±{}%", duration(d), percent),
+        }
+    }
+}
+
+/// parse_duration_range parses "A..B", "A..=B", "A±B" or "A±P%" with
+/// durations accepted by `parse_duration`, "+-" may be written for "±".
+/// "A..B" excludes B and "A..=B" includes it, like Rust ranges and
+/// `parse_size_range`. The percentage is between 0 and 100, and a single
+/// duration is a range without jitter.
+///
+/// parse_duration_range("1s..=5s") -> Ok(1s..=5s)
+/// parse_duration_range("30s+-10%") -> Ok(30s±10%)
+///
+/// # Errors
+///
+/// Return `Error` if a duration is not valid, or `Error::InvalidRange` if the
+/// range is malformed or empty.
+pub fn parse_duration_range(text: &str) -> Result<DurationRange, Error> {
+    let text = text.trim();
+
+    if let Some((min, max)) = text.split_once("..=") {
+        let (min, max) = (parse_duration(min.trim())?, parse_duration(max.trim())?);
+        if min > max {
+            return Err(Error::InvalidRange);
+        }
+        return Ok(DurationRange::Between(min, max));
+    }
+
+    if let Some((min, end)) = text.split_once("..") {
+        let (min, end) = (parse_duration(min.trim())?, parse_duration(end.trim())?);
+        if min >= end {
+            return Err(Error::InvalidRange);
+        }
+        return Ok(DurationRange::Exclusive(min, end));
+    }
+
+    let Some((d, jitter)) = text.split_once('±').or_else(|| text.split_once("+-")) else {
+        return Ok(DurationRange::Jitter(parse_duration(text)?, Duration::ZERO));
+    };
+
+    let d = parse_duration(d.trim())?;
+    match jitter.trim().strip_suffix('%').map(str::trim) {
+        // Only plain decimals like `bytes::parse_size_spec`, parse::<f64>
+        // would accept "+5", "inf" and "1e1"
+        Some(percent) if !percent.bytes().all(|c| c.is_ascii_digit() || c == b'.') => {
+            Err(Error::InvalidRange)
+        }
+        Some(percent) => match percent.parse::<f64>() {
+            Ok(percent) if (0.0..=100.0).contains(&percent) => {
+                Ok(DurationRange::Percent(d, percent))
+            }
+            _ => Err(Error::InvalidRange),
+        },
+        None => Ok(DurationRange::Jitter(d, parse_duration(jitter.trim())?)),
+    }
 }
 
-fn parse(text: &str) -> Result<i64, Error> {
+/// parse_nanos parses a duration string into its sign and magnitude in nanoseconds,
+/// the magnitude is at most `MAX_NANOS`.
+const fn parse_nanos(text: &str) -> Result<(bool, u128), Error> {
     // [-+]?([0-9]*(\.[0-9]*)?[a-z]+)+
-    let mut d = 0u64;
+    let mut d = 0u128;
     let mut neg = false;
     let mut s = text.as_bytes();
 
@@ -115,13 +615,13 @@ fn parse(text: &str) -> Result<i64, Error> {
         let c = s[0];
         if c == b'-' || c == b'+' {
             neg = c == b'-';
-            s = &s[1..];
+            s = s.split_at(1).1;
         }
     }
 
     // Special case: if all that is left is "0", this is zero
     if s.len() == 1 && s[0] == b'0' {
-        return Ok(0);
+        return Ok((false, 0));
     }
 
     if s.is_empty() {
@@ -140,15 +640,18 @@ fn parse(text: &str) -> Result<i64, Error> {
 
         // Consume [0-9]*
         let pl = s.len();
-        let (l, remain) = leading_int(s)?;
-        let mut v = l;
+        let (l, remain) = match leading_int(s) {
+            Ok(v) => v,
+            Err(err) => return Err(err),
+        };
+        let mut v = l as u128;
         s = remain;
         let pre = pl != s.len();
 
         // Consume (\.[0-9]*)?
         let mut post = false;
         if !s.is_empty() && s[0] == b'.' {
-            s = &s[1..];
+            s = s.split_at(1).1;
             let pl = s.len();
             let (lf, ls, remain) = leading_fraction(s);
             f = lf;
@@ -175,8 +678,8 @@ fn parse(text: &str) -> Result<i64, Error> {
         if i == 0 {
             return Err(Error::MissingUnit);
         }
-        let u = &s[..i];
-        s = &s[i..];
+        let (u, remain) = s.split_at(i);
+        s = remain;
         let unit = match u {
             [b'n', b's'] => NANOSECOND,
             [b'u', b's'] => MICROSECOND,
@@ -191,43 +694,36 @@ fn parse(text: &str) -> Result<i64, Error> {
             [b'd'] => DAY,
             [b'w'] => WEEK,
             _ => 0,
-        } as u64;
+        } as u128;
         if unit == 0 {
             return Err(Error::UnknownUnit);
         }
 
-        if v > (1 << 63) / unit {
-            return Err(Error::InvalidDuration);
-        }
-
+        // v < 2^64 and unit < 2^60, the product can't overflow
         v *= unit;
         if f > 0 {
-            // float64 is needed to be nanosecond accurate for fractions of hours.
-            // v >= 0 && (f * unit / scale) <= 3.6e+12 (ns/h, h is the largest unit)
-            v = v
-                .checked_add((f as f64 * (unit as f64 / scale)) as u64)
-                .ok_or(Error::InvalidDuration)?;
+            // float64 is needed to be nanosecond accurate for fractions of weeks.
+            // v >= 0 && (f * unit / scale) <= 6.048e+14 (ns/w, w is the largest unit)
+            v += (f as f64 * (unit as f64 / scale)) as u128;
         }
 
         d += v;
-        if d > 1 << 63 {
+        if d > MAX_NANOS {
             return Err(Error::InvalidDuration);
         }
     }
 
-    if neg {
-        return Ok(-(d as i64));
-    }
-
-    if d > (1 << 63) - 1 {
-        return Err(Error::InvalidDuration);
-    }
-
-    Ok(d as i64)
+    Ok((neg && d != 0, d))
 }
 
+/// duration returns a string representing the duration, like `to_string`, but
+/// without any limit on the range, and with days and weeks as `parse_duration`
+/// accepts them.
+///
+/// duration(&Duration::from_secs(90000)) -> 1d1h
+/// duration(&Duration::MAX) -> 30500568904943w7h15.999999999s
 pub fn duration(d: &Duration) -> String {
-    to_string(d.as_nanos() as i64)
+    format(false, d.as_nanos(), true)
 }
 
 /// duration returns a string representing the duration in the form "72h3m0.5s".
@@ -235,15 +731,17 @@ pub fn duration(d: &Duration) -> String {
 /// second format use a smaller unit (milli-, micro-, or nanoseconds) to ensure
 /// that the leading digit is non-zero. The zero duration formats as 0s.
 pub fn to_string(d: i64) -> String {
-    // Largest time is 2540400h10m10.000000000s
-    let mut w = 32;
-    let mut buf = [0u8; 32];
-    let neg = d < 0;
+    format(d < 0, u128::from(d.unsigned_abs()), false)
+}
 
-    let d = d as u64;
-    let mut u = d;
+/// format formats a number of nanoseconds, the largest unit is hours unless
+/// `weeks` is set.
+fn format(neg: bool, mut u: u128, weeks: bool) -> String {
+    // Largest time is -5124095576030431h59m59.999999999s
+    let mut w = 40;
+    let mut buf = [0u8; 40];
 
-    if u < SECOND as u64 {
+    if u < SECOND as u128 {
         // Special case: if duration is smaller thant a second,
         // use smaller units, like 1.2ms
         w -= 1;
@@ -252,11 +750,11 @@ pub fn to_string(d: i64) -> String {
 
         let prec = if u == 0 {
             return "0s".to_string();
-        } else if u < MICROSECOND as u64 {
+        } else if u < MICROSECOND as u128 {
             // print nanoseconds
             buf[w] = b'n';
             0
-        } else if u < MILLISECOND as u64 {
+        } else if u < MILLISECOND as u128 {
             // print microseconds
 
             /*
@@ -279,7 +777,7 @@ pub fn to_string(d: i64) -> String {
         u = _u;
         w = fmt_int(&mut buf[..w], u);
     } else {
-        if u % SECOND as u64 != 0 {
+        if !u.is_multiple_of(SECOND as u128) {
             w -= 1;
             buf[w] = b's';
 
@@ -290,7 +788,7 @@ pub fn to_string(d: i64) -> String {
             // u is now integer seconds
             w = fmt_int(&mut buf[..w], u % 60);
         } else {
-            u /= SECOND as u64;
+            u /= SECOND as u128;
 
             let n = u % 60;
             if n != 0 {
@@ -306,7 +804,7 @@ pub fn to_string(d: i64) -> String {
 
         // u is now integer minutes
         if u > 0 {
-            if u % 60 != 0 {
+            if !u.is_multiple_of(60) {
                 w -= 1;
                 buf[w] = b'm';
                 w = fmt_int(&mut buf[..w], u % 60);
@@ -315,8 +813,30 @@ pub fn to_string(d: i64) -> String {
             u /= 60;
 
             // u is now integer hours
-            // Stop at hours because days can be different lengths.
-            if u > 0 {
+            if u > 0 && weeks {
+                if !u.is_multiple_of(24) {
+                    w -= 1;
+                    buf[w] = b'h';
+                    w = fmt_int(&mut buf[..w], u % 24);
+                }
+
+                // u is now integer days
+                u /= 24;
+                if !u.is_multiple_of(7) {
+                    w -= 1;
+                    buf[w] = b'd';
+                    w = fmt_int(&mut buf[..w], u % 7);
+                }
+
+                // u is now integer weeks
+                u /= 7;
+                if u > 0 {
+                    w -= 1;
+                    buf[w] = b'w';
+                    w = fmt_int(&mut buf[..w], u);
+                }
+            } else if u > 0 {
+                // Go stops at hours because days can be different lengths
                 w -= 1;
                 buf[w] = b'h';
                 w = fmt_int(&mut buf[..w], u)
@@ -332,11 +852,107 @@ pub fn to_string(d: i64) -> String {
     String::from_utf8_lossy(&buf[w..]).to_string()
 }
 
+/// ClockFields is the leading field of a clock, it absorbs the larger units.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ClockFields {
+    /// "MM:SS", e.g. "90:05"
+    Minutes,
+    /// "HH:MM:SS", e.g. "01:30:05"
+    #[default]
+    Hours,
+    /// "Dd HH:MM:SS", e.g. "1d 02:03:04"
+    Days,
+}
+
+/// Clock formats durations like a clock, "01:02:03.450".
+///
+/// Clock::new().fields(ClockFields::Minutes).precision(1).format(&d) -> 90:05.2
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Clock {
+    fields: ClockFields,
+    precision: u8,
+}
+
+impl Clock {
+    /// new returns a clock formatting "HH:MM:SS".
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            fields: ClockFields::Hours,
+            precision: 0,
+        }
+    }
+
+    /// fields sets the leading field.
+    #[must_use]
+    pub const fn fields(mut self, fields: ClockFields) -> Self {
+        self.fields = fields;
+        self
+    }
+
+    /// precision sets the digits of the fraction of seconds, at most 9. The
+    /// fraction is truncated so the seconds never round up to 60.
+    #[must_use]
+    pub const fn precision(mut self, precision: u8) -> Self {
+        self.precision = if precision > 9 { 9 } else { precision };
+        self
+    }
+
+    /// format returns the duration as a clock, it can be parsed back with
+    /// `parse_clock`.
+    #[must_use]
+    pub fn format(&self, d: &Duration) -> String {
+        let (minutes, seconds) = (d.as_secs() / 60, d.as_secs() % 60);
+        let mut s = match self.fields {
+            ClockFields::Minutes => format!("{:02}:{:02}", minutes, seconds),
+            ClockFields::Hours => {
+                format!("{:02}:{:02}:{:02}", minutes / 60, minutes % 60, seconds)
+            }
+            ClockFields::Days => format!(
+                "{}d {:02}:{:02}:{:02}",
+                minutes / (24 * 60),
+                minutes / 60 % 24,
+                minutes % 60,
+                seconds
+            ),
+        };
+
+        if self.precision > 0 {
+            let precision = usize::from(self.precision);
+            let fraction = d.subsec_nanos() / 10u32.pow(9 - u32::from(self.precision));
+            s += &format!(".{:0precision$}", fraction);
+        }
+        s
+    }
+}
+
+/// clock returns the duration as "HH:MM:SS", see `Clock` for other forms.
+///
+/// clock(&Duration::from_secs(5400)) -> 01:30:00
+pub fn clock(d: &Duration) -> String {
+    Clock::new().format(d)
+}
+
+/// parse_signed_nanos parses a possibly negative duration string into nanoseconds.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn parse_signed_nanos(text: &str) -> Result<i128, Error> {
+    let (neg, d) = parse_nanos(text)?;
+    let d = d as i128;
+
+    Ok(if neg { -d } else { d })
+}
+
+/// format_signed formats a possibly negative number of nanoseconds, like `to_string`.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn format_signed(nanos: i128) -> String {
+    format(nanos < 0, nanos.unsigned_abs(), false)
+}
+
 // fmt_frac formats the fraction of v / 10 ** prec (e.g., ".12345") into the
 // tail of buf, omitting trailing zeros. It omits the decimal point too when
 // the fraction is 0. It returns the index where the output bytes begin and
 // the value v / 10 ** prec
-fn fmt_frac(buf: &mut [u8], mut v: u64, prec: i32) -> (usize, u64) {
+fn fmt_frac(buf: &mut [u8], mut v: u128, prec: i32) -> (usize, u128) {
     // Omit trailing zeros up to and including decimal point
     let mut w = buf.len();
     let mut print = false;
@@ -361,7 +977,7 @@ fn fmt_frac(buf: &mut [u8], mut v: u64, prec: i32) -> (usize, u64) {
 
 // fmt_int formats v into the tail of buf.
 // It returns the index where the output begins.
-fn fmt_int(buf: &mut [u8], mut v: u64) -> usize {
+fn fmt_int(buf: &mut [u8], mut v: u128) -> usize {
     let mut w = buf.len();
     if v == 0 {
         w -= 1;
@@ -377,76 +993,602 @@ fn fmt_int(buf: &mut [u8], mut v: u64) -> usize {
     w
 }
 
+/// Deserializes durations from strings like "1h30m", integers and floats
+/// are a number of seconds, see `serde::millis` for milliseconds.
 #[cfg(feature = "serde")]
 pub mod serde {
-    use std::borrow::Cow;
+    use std::fmt::Formatter;
+    use std::time::Duration;
 
-    use serde_core::de::Error;
-    use serde_core::{Deserialize, Deserializer, Serializer};
+    use serde_core::de::{Error, Unexpected, Visitor};
+    use serde_core::{Deserializer, Serializer};
 
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    use super::{SECOND, parse_signed_nanos};
     use super::{duration, parse_duration};
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(
+    /// Accepts a duration string, or a number of `unit`s.
+    pub(super) struct DurationVisitor {
+        pub(super) unit: Duration,
+    }
+
+    impl DurationVisitor {
+        pub(super) const SECONDS: Self = Self {
+            unit: Duration::from_secs(1),
+        };
+        pub(super) const MILLIS: Self = Self {
+            unit: Duration::from_millis(1),
+        };
+    }
+
+    impl<'de> Visitor<'de> for DurationVisitor {
+        type Value = Duration;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            let unit = if self.unit == Duration::from_millis(1) {
+                "milliseconds"
+            } else {
+                "seconds"
+            };
+            write!(
+                f,
+                "a number of {} or a duration string like \"1h30m\"",
+                unit
+            )
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            let nanos = u128::from(v) * self.unit.as_nanos();
+            match u64::try_from(nanos / 1_000_000_000) {
+                Ok(secs) => Ok(Duration::new(secs, (nanos % 1_000_000_000) as u32)),
+                Err(_) => Err(E::invalid_value(Unexpected::Unsigned(v), &self)),
+            }
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            match u64::try_from(v) {
+                Ok(v) => self.visit_u64(v),
+                Err(_) => Err(E::invalid_value(Unexpected::Signed(v), &self)),
+            }
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            Duration::try_from_secs_f64(v * self.unit.as_secs_f64())
+                .map_err(|_| E::invalid_value(Unexpected::Float(v), &self))
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse_duration(v).map_err(Error::custom)
+        }
+    }
+
+    /// Accepts a possibly negative duration string, or a number of seconds,
+    /// the value is in nanoseconds.
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    pub(super) struct SignedVisitor;
+
+    #[cfg(any(feature = "chrono", feature = "time"))]
+    impl<'de> Visitor<'de> for SignedVisitor {
+        type Value = i128;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(
+                f,
+                "a number of seconds or a duration string like \"-1h30m\""
+            )
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(i128::from(v) * SECOND as i128)
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(i128::from(v) * SECOND as i128)
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            if v.is_finite() && v.abs() <= u64::MAX as f64 {
+                Ok((v * SECOND as f64) as i128)
+            } else {
+                Err(E::invalid_value(Unexpected::Float(v), &self))
+            }
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse_signed_nanos(v).map_err(Error::custom)
+        }
+    }
+
+    /// deserialize_any reads a number or a string from self-describing formats
+    /// like JSON, but only the string written by `serialize` from formats like
+    /// bincode, which can't tell which one comes next.
+    pub(super) fn deserialize_any<'de, D: Deserializer<'de>, V: Visitor<'de>>(
         deserializer: D,
-    ) -> Result<std::time::Duration, D::Error> {
-        let s: Cow<'de, str> = Deserialize::deserialize(deserializer)?;
-        parse_duration(s.as_ref()).map_err(Error::custom)
+        visitor: V,
+    ) -> Result<V::Value, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(visitor)
+        } else {
+            deserializer.deserialize_str(visitor)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        deserialize_any(deserializer, DurationVisitor::SECONDS)
     }
 
-    pub fn serialize<S: Serializer>(d: &std::time::Duration, s: S) -> Result<S::Ok, S::Error> {
+    pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
         s.serialize_str(&duration(d))
     }
+
+    /// Matches the strings accepted by `parse_duration`.
+    #[cfg(feature = "schemars")]
+    const PATTERN: &str = concat!(
+        r"^([-+]?0|-((0+(\.0*)?|\.0+)(ns|us|µs|μs|ms|s|m|h|d|w))+",
+        r"|\+?(([0-9]+(\.[0-9]*)?|\.[0-9]+)(ns|us|µs|μs|ms|s|m|h|d|w))+",
+        // Clocks of `parse_clock`, "MM:SS", "HH:MM:SS" or with days
+        r"|([0-9]+(:[0-5]?[0-9]){1,2}|[0-9]+(:|-|d +)([01]?[0-9]|2[0-3])(:[0-5]?[0-9]){2})",
+        r"(\.[0-9]*)?)$",
+    );
+
+    /// `json_schema` describes the durations accepted by `deserialize`, to be used with
+    /// `#[schemars(schema_with = "humanize::duration::serde::json_schema")]`.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schema(&["string", "number"], "seconds")
+    }
+
+    #[cfg(feature = "schemars")]
+    pub(super) fn schema(types: &[&str], unit: &str) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": format!("A duration like \"1h30m\" or \"500ms\", or a number of {}", unit),
+            "type": types,
+            "pattern": PATTERN,
+            "minimum": 0,
+            "examples": ["1h30m", "500ms", 30],
+        })
+    }
+
+    /// The same as `serde`, but integers and floats are a number of milliseconds.
+    pub mod millis {
+        use std::time::Duration;
+
+        use serde_core::Deserializer;
+
+        use super::{DurationVisitor, deserialize_any};
+
+        pub use super::serialize;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Duration, D::Error> {
+            deserialize_any(deserializer, DurationVisitor::MILLIS)
+        }
+
+        /// `json_schema` describes the durations accepted by `deserialize`.
+        #[cfg(feature = "schemars")]
+        pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            super::schema(&["string", "number"], "milliseconds")
+        }
+    }
 }
 
 #[cfg(feature = "serde")]
 pub mod serde_option {
-    use std::borrow::Cow;
+    use std::fmt::Formatter;
+    use std::time::Duration;
 
-    use serde_core::de::Error;
-    use serde_core::{Deserialize, Deserializer, Serializer};
+    use serde_core::de::Visitor;
+    use serde_core::{Deserializer, Serializer};
 
-    use super::{duration, parse_duration};
+    use super::duration;
+    use super::serde::{DurationVisitor, deserialize_any};
 
-    pub fn deserialize<'de, D: Deserializer<'de>>(
-        deserializer: D,
-    ) -> Result<Option<std::time::Duration>, D::Error> {
-        let s: Option<Cow<'de, str>> = Deserialize::deserialize(deserializer)?;
+    /// Accepts null, or whatever `V` accepts.
+    pub(super) struct OptionVisitor<V>(pub(super) V);
 
-        match s {
-            Some(text) => {
-                let duration = parse_duration(&text).map_err(Error::custom)?;
-                Ok(Some(duration))
-            }
-            None => Ok(None),
+    impl<'de, V: Visitor<'de>> Visitor<'de> for OptionVisitor<V> {
+        type Value = Option<V::Value>;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            self.0.expecting(f)?;
+            write!(f, " or null")
         }
+
+        fn visit_none<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_unit<E>(self) -> Result<Self::Value, E> {
+            Ok(None)
+        }
+
+        fn visit_some<D: Deserializer<'de>>(
+            self,
+            deserializer: D,
+        ) -> Result<Self::Value, D::Error> {
+            deserialize_any(deserializer, self.0).map(Some)
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        deserializer.deserialize_option(OptionVisitor(DurationVisitor::SECONDS))
     }
 
-    pub fn serialize<S: Serializer>(
-        d: &Option<std::time::Duration>,
-        s: S,
-    ) -> Result<S::Ok, S::Error> {
+    pub fn serialize<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
         match d {
-            Some(d) => s.serialize_str(&duration(d)),
+            Some(d) => s.serialize_some(&duration(d)),
             None => s.serialize_none(),
         }
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::time::Duration;
 
-    #[test]
-    fn test_leading_int() {
-        let (x, remain) = leading_int("12h".as_bytes()).unwrap();
-        assert_eq!(x, 12u64);
-        assert_eq!(String::from_utf8_lossy(remain), "h".to_string());
+    /// `json_schema` describes the durations accepted by `deserialize`, to be used with
+    /// `#[schemars(schema_with = "humanize::duration::serde_option::json_schema")]`.
+    #[cfg(feature = "schemars")]
+    pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        super::serde::schema(&["string", "number", "null"], "seconds")
     }
 
-    #[test]
-    fn test_leading_int_overflow() {
-        let err = leading_int("999999999999999999999".as_bytes()).unwrap_err();
+    /// The same as `serde_option`, but integers and floats are a number of milliseconds.
+    pub mod millis {
+        use std::time::Duration;
+
+        use serde_core::Deserializer;
+
+        use super::{DurationVisitor, OptionVisitor};
+
+        pub use super::serialize;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            deserializer.deserialize_option(OptionVisitor(DurationVisitor::MILLIS))
+        }
+
+        /// `json_schema` describes the durations accepted by `deserialize`.
+        #[cfg(feature = "schemars")]
+        pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            crate::duration::serde::schema(&["string", "number", "null"], "milliseconds")
+        }
+    }
+}
+
+/// Parsing and formatting of `chrono::TimeDelta`, which unlike `std::time::Duration` can be negative.
+#[cfg(feature = "chrono")]
+pub mod chrono {
+    use ::chrono::TimeDelta;
+
+    use super::{Error, SECOND, format_signed, parse_signed_nanos};
+
+    /// parse_duration parses a duration string like `duration::parse_duration`,
+    /// negative durations are allowed.
+    ///
+    /// parse_duration("-1h30m") -> Ok(TimeDelta::minutes(-90))
+    pub fn parse_duration(text: &str) -> Result<TimeDelta, Error> {
+        from_nanos(parse_signed_nanos(text)?).ok_or(Error::InvalidDuration)
+    }
+
+    /// duration returns a string representing the duration, like `duration::to_string`.
+    ///
+    /// duration(&TimeDelta::minutes(-90)) -> -1h30m
+    pub fn duration(d: &TimeDelta) -> String {
+        format_signed(to_nanos(d))
+    }
+
+    fn from_nanos(nanos: i128) -> Option<TimeDelta> {
+        let secs = i64::try_from(nanos.div_euclid(SECOND as i128)).ok()?;
+        TimeDelta::new(secs, nanos.rem_euclid(SECOND as i128) as u32)
+    }
+
+    fn to_nanos(d: &TimeDelta) -> i128 {
+        i128::from(d.num_seconds()) * SECOND as i128 + i128::from(d.subsec_nanos())
+    }
+
+    #[cfg(feature = "serde")]
+    pub mod serde {
+        use ::chrono::TimeDelta;
+
+        use serde_core::de::Error;
+        use serde_core::{Deserializer, Serializer};
+
+        use super::{duration, from_nanos};
+        use crate::duration::serde::{SignedVisitor, deserialize_any};
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<TimeDelta, D::Error> {
+            let nanos = deserialize_any(deserializer, SignedVisitor)?;
+            from_nanos(nanos).ok_or_else(|| Error::custom(super::Error::InvalidDuration))
+        }
+
+        pub fn serialize<S: Serializer>(d: &TimeDelta, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&duration(d))
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub mod serde_option {
+        use ::chrono::TimeDelta;
+
+        use serde_core::de::Error;
+        use serde_core::{Deserializer, Serializer};
+
+        use super::{duration, from_nanos};
+        use crate::duration::serde::SignedVisitor;
+        use crate::duration::serde_option::OptionVisitor;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<TimeDelta>, D::Error> {
+            match deserializer.deserialize_option(OptionVisitor(SignedVisitor))? {
+                Some(nanos) => from_nanos(nanos)
+                    .map(Some)
+                    .ok_or_else(|| Error::custom(super::Error::InvalidDuration)),
+                None => Ok(None),
+            }
+        }
+
+        pub fn serialize<S: Serializer>(d: &Option<TimeDelta>, s: S) -> Result<S::Ok, S::Error> {
+            match d {
+                Some(d) => s.serialize_some(&duration(d)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+}
+
+/// Parsing and formatting of `time::Duration`, which unlike `std::time::Duration` can be negative.
+#[cfg(feature = "time")]
+pub mod time {
+    use ::time::Duration;
+
+    use super::{Error, SECOND, format_signed, parse_signed_nanos};
+
+    /// parse_duration parses a duration string like `duration::parse_duration`,
+    /// negative durations are allowed.
+    ///
+    /// parse_duration("-1h30m") -> Ok(Duration::minutes(-90))
+    pub fn parse_duration(text: &str) -> Result<Duration, Error> {
+        from_nanos(parse_signed_nanos(text)?).ok_or(Error::InvalidDuration)
+    }
+
+    /// duration returns a string representing the duration, like `duration::to_string`.
+    ///
+    /// duration(&Duration::minutes(-90)) -> -1h30m
+    pub fn duration(d: &Duration) -> String {
+        format_signed(to_nanos(d))
+    }
+
+    fn from_nanos(nanos: i128) -> Option<Duration> {
+        let secs = i64::try_from(nanos / SECOND as i128).ok()?;
+        Some(Duration::new(secs, (nanos % SECOND as i128) as i32))
+    }
+
+    fn to_nanos(d: &Duration) -> i128 {
+        d.whole_nanoseconds()
+    }
+
+    #[cfg(feature = "serde")]
+    pub mod serde {
+        use ::time::Duration;
+
+        use serde_core::de::Error;
+        use serde_core::{Deserializer, Serializer};
+
+        use super::{duration, from_nanos};
+        use crate::duration::serde::{SignedVisitor, deserialize_any};
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Duration, D::Error> {
+            let nanos = deserialize_any(deserializer, SignedVisitor)?;
+            from_nanos(nanos).ok_or_else(|| Error::custom(super::Error::InvalidDuration))
+        }
+
+        pub fn serialize<S: Serializer>(d: &Duration, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_str(&duration(d))
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    pub mod serde_option {
+        use ::time::Duration;
+
+        use serde_core::de::Error;
+        use serde_core::{Deserializer, Serializer};
+
+        use super::{duration, from_nanos};
+        use crate::duration::serde::SignedVisitor;
+        use crate::duration::serde_option::OptionVisitor;
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Option<Duration>, D::Error> {
+            match deserializer.deserialize_option(OptionVisitor(SignedVisitor))? {
+                Some(nanos) => from_nanos(nanos)
+                    .map(Some)
+                    .ok_or_else(|| Error::custom(super::Error::InvalidDuration)),
+                None => Ok(None),
+            }
+        }
+
+        pub fn serialize<S: Serializer>(d: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+            match d {
+                Some(d) => s.serialize_some(&duration(d)),
+                None => s.serialize_none(),
+            }
+        }
+    }
+}
+
+/// clap value parser for durations. Like `bytes::clap`, it reports no
+/// `possible_values`, since clap would list examples as the only valid values.
+#[cfg(feature = "clap")]
+pub mod clap {
+    use std::ffi::OsStr;
+    use std::time::Duration;
+
+    use clap::builder::TypedValueParser;
+    use clap::{Arg, Command, Error};
+
+    use super::parse_duration;
+    use crate::cli::{invalid_value, to_str};
+
+    /// `DurationValueParser` parses durations with `parse_duration`, e.g. `--timeout 1m30s`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct DurationValueParser;
+
+    impl TypedValueParser for DurationValueParser {
+        type Value = Duration;
+
+        fn parse_ref(
+            &self,
+            cmd: &Command,
+            arg: Option<&Arg>,
+            value: &OsStr,
+        ) -> Result<Self::Value, Error> {
+            let value = to_str(cmd, value)?;
+            parse_duration(value).map_err(|err| {
+                let hint = "valid units are ns, us, ms, s, m, h, d, w, e.g. 1h30m";
+                invalid_value(cmd, arg, value, err, hint)
+            })
+        }
+    }
+}
+
+/// `HumanDuration` is a `serde_with` adapter for durations, it can be nested in
+/// `Vec`, `Option`, maps and so on. Durations are serialized with `duration`,
+/// and deserialized from strings, or numbers of seconds like `duration::serde`.
+///
+/// #[serde_as(as = "HashMap<_, HumanDuration>")]
+/// timeouts: HashMap<String, Duration>,
+#[cfg(feature = "serde_with")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct HumanDuration;
+
+/// BoundedDuration is `HumanDuration` which rejects durations outside
+/// `Bounds`, given in milliseconds. `u64::MAX` is no upper bound, and a
+/// multiple of 0 accepts any duration.
+///
+/// #[serde_as(as = "BoundedDuration<1000, 3600000>")]
+/// timeout: Duration,
+#[cfg(feature = "serde_with")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BoundedDuration<
+    const MIN_MS: u64 = 0,
+    const MAX_MS: u64 = { u64::MAX },
+    const MULTIPLE_MS: u64 = 0,
+>;
+
+#[cfg(feature = "serde_with")]
+impl<const MIN_MS: u64, const MAX_MS: u64, const MULTIPLE_MS: u64>
+    BoundedDuration<MIN_MS, MAX_MS, MULTIPLE_MS>
+{
+    /// BOUNDS are the bounds this adapter checks.
+    pub const BOUNDS: Bounds = Bounds::new()
+        .min(Duration::from_millis(MIN_MS))
+        .max(if MAX_MS == u64::MAX {
+            Duration::MAX
+        } else {
+            Duration::from_millis(MAX_MS)
+        })
+        .multiple_of(Duration::from_millis(MULTIPLE_MS));
+}
+
+#[cfg(feature = "serde_with")]
+mod serde_with_human_duration {
+    use std::time::Duration;
+
+    use serde_core::de::Error;
+    use serde_core::{Deserializer, Serializer};
+    use serde_with::{DeserializeAs, SerializeAs};
+
+    use super::serde::{DurationVisitor, deserialize_any};
+    use super::{BoundedDuration, HumanDuration};
+
+    impl SerializeAs<Duration> for HumanDuration {
+        fn serialize_as<S: Serializer>(source: &Duration, s: S) -> Result<S::Ok, S::Error> {
+            super::serde::serialize(source, s)
+        }
+    }
+
+    impl<'de> DeserializeAs<'de, Duration> for HumanDuration {
+        fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+            deserialize_any(deserializer, DurationVisitor::SECONDS)
+        }
+    }
+
+    impl<const MIN_MS: u64, const MAX_MS: u64, const MULTIPLE_MS: u64> SerializeAs<Duration>
+        for BoundedDuration<MIN_MS, MAX_MS, MULTIPLE_MS>
+    {
+        fn serialize_as<S: Serializer>(source: &Duration, s: S) -> Result<S::Ok, S::Error> {
+            super::serde::serialize(source, s)
+        }
+    }
+
+    impl<'de, const MIN_MS: u64, const MAX_MS: u64, const MULTIPLE_MS: u64>
+        DeserializeAs<'de, Duration> for BoundedDuration<MIN_MS, MAX_MS, MULTIPLE_MS>
+    {
+        fn deserialize_as<D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+            let d = deserialize_any(deserializer, DurationVisitor::SECONDS)?;
+            Self::BOUNDS.check(d).map_err(D::Error::custom)
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    impl serde_with::schemars_1::JsonSchemaAs<Duration> for HumanDuration {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            "HumanDuration".into()
+        }
+
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            "humanize::duration::HumanDuration".into()
+        }
+
+        fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            super::serde::json_schema(generator)
+        }
+    }
+
+    // Bounds can't be expressed on strings, so this is the same schema
+    #[cfg(feature = "schemars")]
+    impl<const MIN_MS: u64, const MAX_MS: u64, const MULTIPLE_MS: u64>
+        serde_with::schemars_1::JsonSchemaAs<Duration>
+        for BoundedDuration<MIN_MS, MAX_MS, MULTIPLE_MS>
+    {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            "HumanDuration".into()
+        }
+
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            "humanize::duration::HumanDuration".into()
+        }
+
+        fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            super::serde::json_schema(generator)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_leading_int() {
+        let (x, remain) = leading_int("12h".as_bytes()).unwrap();
+        assert_eq!(x, 12u64);
+        assert_eq!(String::from_utf8_lossy(remain), "h".to_string());
+    }
+
+    #[test]
+    fn test_leading_int_overflow() {
+        let err = leading_int("999999999999999999999".as_bytes()).unwrap_err();
+        assert_eq!(err, Error::BadInteger);
+        let err = leading_int("18446744073709551616".as_bytes()).unwrap_err();
         assert_eq!(err, Error::BadInteger)
     }
 
@@ -507,7 +1649,8 @@ mod tests {
             ("9223372036854775.807us", i64::MAX * NANOSECOND),
             ("9223372036s854ms775us807ns", i64::MAX * NANOSECOND),
             // large negative value
-            // todo: ( "-9223372036854775807ns", -1 << 63 + NANOSECOND ),
+            ("-9223372036854775807ns", i64::MIN + NANOSECOND),
+            ("-9223372036854775808ns", i64::MIN),
             // huge string; issue 15011.
             ("0.100000000000000000000h", 6 * MINUTE),
             // This value tests the first overflow check in leadingFraction.
@@ -518,7 +1661,8 @@ mod tests {
         ];
 
         for (input, want) in tests {
-            let got = parse(input).expect(&format!("parse {input} success"));
+            let got = parse_nanos(input).unwrap_or_else(|_| panic!("parse {input} success"));
+            let want = (want < 0, u128::from(want.unsigned_abs()));
             assert_eq!(got, want, "input: {}", input);
         }
     }
@@ -538,6 +1682,113 @@ mod tests {
         assert_eq!(6, f);
         assert_eq!(10.0, scale);
         assert_eq!(r, "s".as_bytes());
+
+        let (f, scale, r) = leading_fraction("12345678901234567890123s".as_bytes());
+        assert_eq!(1234567890123456789, f);
+        assert_eq!(1e19, scale);
+        assert_eq!(r, "s".as_bytes());
+    }
+
+    #[test]
+    fn parse_const() {
+        const TIMEOUT: Duration = crate::duration!("1h30m");
+        assert_eq!(TIMEOUT, Duration::from_secs(90 * 60));
+        assert_eq!(crate::duration!("1.5µs"), Duration::from_nanos(1500));
+
+        const PARSED: Result<Duration, Error> = parse_duration("1x");
+        assert_eq!(PARSED, Err(Error::UnknownUnit));
+    }
+
+    #[test]
+    fn test_parse_clock() {
+        let tests = [
+            ("00:05", 5 * SECOND),
+            ("90:00", 90 * MINUTE),
+            ("1:30:00", 90 * MINUTE),
+            (
+                " 01:02:03.450 ",
+                HOUR + 2 * MINUTE + 3 * SECOND + 450 * MILLISECOND,
+            ),
+            ("100:00:00", 100 * HOUR),
+            ("0:0:1.", SECOND),
+            ("1:02:03:04", DAY + 2 * HOUR + 3 * MINUTE + 4 * SECOND),
+            ("1-02:03:04", DAY + 2 * HOUR + 3 * MINUTE + 4 * SECOND),
+            ("1d 02:03:04", DAY + 2 * HOUR + 3 * MINUTE + 4 * SECOND),
+            ("2d  00:00:00.5", 2 * DAY + 500 * MILLISECOND),
+            ("00:00.1234567891", 123_456_789),
+        ];
+        for (input, want) in tests {
+            let want = Duration::from_nanos(want as u64);
+            assert_eq!(parse_clock(input), Ok(want), "input: {input}");
+            assert_eq!(parse_duration(input), Ok(want), "input: {input}");
+        }
+
+        let max = "213503982334601:07:00:15.999999999";
+        assert_eq!(parse_clock(max), Ok(Duration::MAX));
+        assert_eq!(
+            parse_clock("213503982334601:07:00:16"),
+            Err(Error::InvalidDuration)
+        );
+        assert_eq!(
+            parse_clock("99999999999999999999:00"),
+            Err(Error::BadInteger)
+        );
+
+        let tests = [
+            "",
+            "5",
+            "1:60",
+            "1:00:60",
+            "1:60:00",
+            "1-24:00:00",
+            "1:24:00:00",
+            "1-00:00",
+            "1d00:00:00",
+            "1:2:3:4:5",
+            "1:001",
+            "-1:00",
+            "1:00s",
+            "1::00",
+            ":30",
+            "1d 1-00:00:00",
+        ];
+        for input in tests {
+            assert_eq!(
+                parse_clock(input),
+                Err(Error::InvalidDuration),
+                "input: {input}"
+            );
+        }
+
+        const TIMEOUT: Duration = crate::duration!("1:30:00");
+        assert_eq!(TIMEOUT, Duration::from_secs(5400));
+    }
+
+    #[test]
+    fn clock() {
+        let d = Duration::new(93_784, 56_789_000);
+        let tests = [
+            (Clock::new(), "26:03:04"),
+            (Clock::new().precision(3), "26:03:04.056"),
+            (Clock::new().precision(12), "26:03:04.056789000"),
+            (Clock::new().fields(ClockFields::Minutes), "1563:04"),
+            (
+                Clock::new().fields(ClockFields::Days).precision(1),
+                "1d 02:03:04.0",
+            ),
+        ];
+        for (clock, want) in tests {
+            assert_eq!(clock.format(&d), want);
+            assert_eq!(parse_clock(want).unwrap().as_secs(), d.as_secs());
+        }
+
+        assert_eq!(super::clock(&Duration::from_secs(5400)), "01:30:00");
+        assert_eq!(super::clock(&Duration::ZERO), "00:00:00");
+        let clock = Clock::new().precision(2);
+        assert_eq!(
+            clock.format(&Duration::from_nanos(59_999_999_999)),
+            "00:00:59.99"
+        );
     }
 
     #[test]
@@ -565,8 +1816,606 @@ mod tests {
         ];
 
         for (want, input) in tests {
-            let d = Duration::from_nanos(input as u64);
-            assert_eq!(duration(&d), want, "want {want}")
+            assert_eq!(to_string(input), want, "want {want}");
+            if input > 0 {
+                assert_eq!(to_string(-input), format!("-{want}"), "want -{want}");
+            }
+        }
+    }
+
+    #[test]
+    fn full_range() {
+        let tests = [
+            (Duration::ZERO, "0s"),
+            (Duration::from_nanos(1), "1ns"),
+            (
+                Duration::from_nanos(i64::MAX as u64 + 1),
+                "15250w1d23h47m16.854775808s",
+            ),
+            (Duration::new(u64::MAX, 0), "30500568904943w7h15s"),
+            (Duration::MAX, "30500568904943w7h15.999999999s"),
+        ];
+
+        for (d, want) in tests {
+            assert_eq!(duration(&d), want);
+            assert_eq!(parse_duration(want).unwrap(), d, "input: {want}");
+        }
+
+        assert_eq!(
+            parse_duration("5124095576030431h15.999999999s").unwrap(),
+            Duration::MAX
+        );
+        assert_eq!(parse_duration("-0s").unwrap(), Duration::ZERO);
+
+        for input in [
+            "-1ns",
+            "-5s",
+            "5124095576030431h16s",
+            "30500568904944w",
+            "18446744073709551615s1s",
+        ] {
+            assert_eq!(
+                parse_duration(input),
+                Err(Error::InvalidDuration),
+                "input: {input}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bounds min is greater than max")]
+    fn inverted_bounds() {
+        let _ = Bounds::new()
+            .max(Duration::from_secs(1))
+            .min(Duration::from_secs(60));
+    }
+
+    #[test]
+    fn bounds() {
+        let bounds = Bounds::new()
+            .min(Duration::from_secs(1))
+            .max(Duration::from_secs(3600))
+            .multiple_of(Duration::from_millis(500));
+
+        assert_eq!(bounds.parse("1s").unwrap(), Duration::from_secs(1));
+        assert_eq!(
+            bounds.parse("59m59.5s").unwrap(),
+            Duration::from_millis(3599500)
+        );
+        assert_eq!(bounds.parse("1x"), Err(Error::UnknownUnit));
+
+        let tests = [
+            ("2h", "must be at most 1h, got 2h"),
+            ("500ms", "must be at least 1s, got 500ms"),
+            ("1.25s", "must be a multiple of 500ms, got 1.25s"),
+        ];
+        for (input, want) in tests {
+            let err = bounds.parse(input).unwrap_err();
+            assert_eq!(err.to_string(), want, "input: {input}");
+        }
+
+        assert_eq!(Bounds::default().check(Duration::MAX), Ok(Duration::MAX));
+    }
+
+    #[test]
+    fn duration_range() {
+        let secs = Duration::from_secs;
+        let tests = [
+            (
+                "1s..=5s",
+                DurationRange::Between(secs(1), secs(5)),
+                "1s..=5s",
+            ),
+            (
+                "1s..=1s",
+                DurationRange::Between(secs(1), secs(1)),
+                "1s..=1s",
+            ),
+            (
+                "1s..5s",
+                DurationRange::Exclusive(secs(1), secs(5)),
+                "1s..5s",
+            ),
+            (
+                " 1m .. 1h30m ",
+                DurationRange::Exclusive(secs(60), secs(5400)),
+                "1m..1h30m",
+            ),
+            ("30s±5s", DurationRange::Jitter(secs(30), secs(5)), "30s±5s"),
+            (
+                "30s +- 5s",
+                DurationRange::Jitter(secs(30), secs(5)),
+                "30s±5s",
+            ),
+            ("30s±10%", DurationRange::Percent(secs(30), 10.0), "30s±10%"),
+            (
+                "1s±12.5%",
+                DurationRange::Percent(secs(1), 12.5),
+                "1s±12.5%",
+            ),
+            (
+                "30s",
+                DurationRange::Jitter(secs(30), Duration::ZERO),
+                "30s",
+            ),
+        ];
+        for (input, range, want) in tests {
+            assert_eq!(parse_duration_range(input), Ok(range), "input: {input}");
+            assert_eq!(range.to_string(), want, "input: {input}");
+            assert_eq!(parse_duration_range(want), Ok(range), "input: {input}");
+        }
+
+        let range = parse_duration_range("30s±10%").unwrap();
+        assert_eq!((range.min(), range.max()), (secs(27), secs(33)));
+        assert_eq!(range.sample(0.0), secs(27));
+        assert_eq!(range.sample(0.5), secs(30));
+        assert_eq!(range.sample(1.0), secs(33));
+        assert_eq!(range.sample(2.0), secs(33));
+        assert_eq!(range.sample(f64::NAN), secs(27));
+        assert!(range.contains(secs(33)));
+        assert!(!range.contains(Duration::from_millis(26999)));
+
+        let range = parse_duration_range("1s±5s").unwrap();
+        assert_eq!((range.min(), range.max()), (Duration::ZERO, secs(6)));
+        assert_eq!(range.sample(0.25), Duration::from_millis(1500));
+
+        let range = parse_duration_range("0s..=213503982334601:07:00:15.999999999").unwrap();
+        assert_eq!(range.max(), Duration::MAX);
+        assert_eq!(range.sample(0.0), Duration::ZERO);
+        assert_eq!(range.sample(1.0), Duration::MAX);
+        assert!(range.sample(0.5) < Duration::MAX);
+
+        let range = parse_duration_range("1s..=5s").unwrap();
+        assert!(range.contains(secs(5)));
+        assert_eq!(range.sample(1.0), secs(5));
+
+        let range = parse_duration_range("1s..5s").unwrap();
+        assert!(range.contains(secs(1)));
+        assert!(!range.contains(secs(5)));
+        assert_eq!(range.max(), secs(5) - Duration::from_nanos(1));
+        assert_eq!(range.sample(1.0), secs(5) - Duration::from_nanos(1));
+
+        let range = parse_duration_range("0s..213503982334601:07:00:15.999999999").unwrap();
+        assert_eq!(range.sample(1.0), Duration::MAX - Duration::from_nanos(1));
+
+        let percents = [
+            (-10.0, secs(1), secs(1)),
+            (f64::NAN, secs(1), secs(1)),
+            (200.0, Duration::ZERO, secs(3)),
+        ];
+        for (percent, min, max) in percents {
+            let range = DurationRange::Percent(secs(1), percent);
+            assert_eq!((range.min(), range.max()), (min, max), "percent: {percent}");
+            assert_eq!(range.sample(1.0), max, "percent: {percent}");
+            assert!(range.contains(secs(1)), "percent: {percent}");
+        }
+        let range = DurationRange::Percent(Duration::MAX, 200.0);
+        assert_eq!((range.min(), range.max()), (Duration::ZERO, Duration::MAX));
+        assert!(range.contains(range.sample(0.5)));
+        assert_eq!(range.to_string(), "30500568904943w7h15.999999999s±200%");
+
+        let tests = [
+            ("5s..1s", Error::InvalidRange),
+            ("5s..=1s", Error::InvalidRange),
+            ("1s..1s", Error::InvalidRange),
+            ("1s..=", Error::InvalidDuration),
+            ("30s±-1%", Error::InvalidRange),
+            ("30s±101%", Error::InvalidRange),
+            ("30s±x%", Error::InvalidRange),
+            ("1s±1e1%", Error::InvalidRange),
+            ("1s±+5%", Error::InvalidRange),
+            ("1s+-+5%", Error::InvalidRange),
+            ("1s±inf%", Error::InvalidRange),
+            ("1s±NaN%", Error::InvalidRange),
+            ("1s..", Error::InvalidDuration),
+            ("30s±", Error::InvalidDuration),
+            ("30±5s", Error::MissingUnit),
+        ];
+        for (input, want) in tests {
+            assert_eq!(parse_duration_range(input), Err(want), "input: {input}");
+        }
+    }
+
+    #[cfg(feature = "env")]
+    #[test]
+    fn env() {
+        let source = [("TIMEOUT", "30s"), ("BAD", "30"), ("NEG", "-1s")];
+        let default = Duration::from_secs(5);
+
+        assert_eq!(
+            from_source(&source, "TIMEOUT").unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(from_source_or(&source, "UNSET", default).unwrap(), default);
+
+        let err = from_source_or(&source, "BAD", default).unwrap_err();
+        assert_eq!(err.to_string(), "invalid BAD=\"30\": missing unit");
+        let err = from_source(&source, "NEG").unwrap_err();
+        assert_eq!(err.to_string(), "invalid NEG=\"-1s\": invalid duration");
+        let err = from_source(&source, "UNSET").unwrap_err();
+        assert_eq!(err.to_string(), "UNSET is not set");
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn round_trip(secs: u64, nanos in 0..1_000_000_000u32) {
+            let d = Duration::new(secs, nanos);
+            proptest::prop_assert_eq!(parse_duration(&duration(&d)).unwrap(), d);
+        }
+
+        #[test]
+        fn round_trip_i64(d: i64) {
+            let (neg, nanos) = parse_nanos(&to_string(d)).unwrap();
+            proptest::prop_assert_eq!((neg, nanos), (d < 0, u128::from(d.unsigned_abs())));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde(with = "super::serde")]
+            timeout: Duration,
+            #[serde(with = "super::serde::millis", default)]
+            interval: Duration,
+            #[serde(with = "super::serde_option", default)]
+            deadline: Option<Duration>,
+            #[serde(with = "super::serde_option::millis", default)]
+            delay: Option<Duration>,
+        }
+
+        let got: Config = serde_json::from_str(
+            r#"{"timeout": 30, "interval": 250, "deadline": 1.5, "delay": "1s"}"#,
+        )
+        .unwrap();
+        let want = Config {
+            timeout: Duration::from_secs(30),
+            interval: Duration::from_millis(250),
+            deadline: Some(Duration::from_millis(1500)),
+            delay: Some(Duration::from_secs(1)),
+        };
+        assert_eq!(got, want);
+
+        let got: Config =
+            serde_json::from_str(r#"{"timeout": "1h30m", "interval": 0.5, "delay": null}"#)
+                .unwrap();
+        let want = Config {
+            timeout: Duration::from_secs(90 * 60),
+            interval: Duration::from_micros(500),
+            deadline: None,
+            delay: None,
+        };
+        assert_eq!(got, want);
+
+        for input in [
+            r#"{"timeout": -1}"#,
+            r#"{"timeout": -0.5}"#,
+            r#"{"timeout": "30"}"#,
+            r#"{"timeout": 1e300}"#,
+            r#"{"timeout": 1, "interval": [1]}"#,
+        ] {
+            assert!(
+                serde_json::from_str::<Config>(input).is_err(),
+                "input: {input}"
+            );
+        }
+
+        // Formats which are not self-describing read the strings back
+        let config = Config {
+            timeout: Duration::from_secs(90 * 60),
+            interval: Duration::from_millis(250),
+            deadline: None,
+            delay: Some(Duration::from_nanos(1500)),
+        };
+        let encoded = bincode::serialize(&config).unwrap();
+        assert_eq!(bincode::deserialize::<Config>(&encoded).unwrap(), config);
+    }
+
+    #[cfg(feature = "serde_with")]
+    #[test]
+    fn serde_with() {
+        use std::collections::HashMap;
+
+        use ::serde_with::serde_as;
+
+        #[serde_as]
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde_as(as = "HashMap<_, HumanDuration>")]
+            timeouts: HashMap<String, Duration>,
+            #[serde_as(as = "Vec<Option<HumanDuration>>")]
+            retries: Vec<Option<Duration>>,
+        }
+
+        let got: Config = serde_json::from_str(
+            r#"{"timeouts": {"read": "1m30s", "write": 5}, "retries": ["1s", null, 0.5]}"#,
+        )
+        .unwrap();
+        let want = Config {
+            timeouts: HashMap::from([
+                ("read".to_string(), Duration::from_secs(90)),
+                ("write".to_string(), Duration::from_secs(5)),
+            ]),
+            retries: vec![
+                Some(Duration::from_secs(1)),
+                None,
+                Some(Duration::from_millis(500)),
+            ],
+        };
+        assert_eq!(got, want);
+
+        let json = serde_json::to_string(&want).unwrap();
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), want);
+        assert!(json.contains(r#"["1s",null,"500ms"]"#), "json: {json}");
+
+        let input = r#"{"timeouts": {"read": "-1s"}, "retries": []}"#;
+        assert!(serde_json::from_str::<Config>(input).is_err());
+    }
+
+    #[cfg(feature = "serde_with")]
+    #[test]
+    fn serde_with_bounded() {
+        use ::serde_with::serde_as;
+
+        #[serde_as]
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde_as(as = "BoundedDuration<1000, 3600000>")]
+            timeout: Duration,
+            #[serde_as(as = "BoundedDuration<0, { u64::MAX }, 1000>")]
+            interval: Duration,
+        }
+
+        let got: Config = serde_json::from_str(r#"{"timeout": "30s", "interval": 60}"#).unwrap();
+        let want = Config {
+            timeout: Duration::from_secs(30),
+            interval: Duration::from_secs(60),
+        };
+        assert_eq!(got, want);
+        assert_eq!(
+            serde_json::from_str::<Config>(&serde_json::to_string(&want).unwrap()).unwrap(),
+            want
+        );
+
+        let tests = [
+            (
+                r#"{"timeout": "2h", "interval": 1}"#,
+                "must be at most 1h, got 2h",
+            ),
+            (
+                r#"{"timeout": 0.5, "interval": 1}"#,
+                "must be at least 1s, got 500ms",
+            ),
+            (
+                r#"{"timeout": 1, "interval": "1.5s"}"#,
+                "must be a multiple of 1s, got 1.5s",
+            ),
+        ];
+        for (input, want) in tests {
+            let err = serde_json::from_str::<Config>(input).unwrap_err();
+            assert!(
+                err.to_string().starts_with(want),
+                "input: {input}, err: {err}"
+            );
+        }
+    }
+
+    #[cfg(feature = "clap")]
+    #[test]
+    fn clap() {
+        use ::clap::{Arg, Command};
+
+        use super::clap::DurationValueParser;
+
+        let cmd = Command::new("test").arg(
+            Arg::new("timeout")
+                .long("timeout")
+                .value_parser(DurationValueParser),
+        );
+
+        let matches = cmd
+            .clone()
+            .try_get_matches_from(["test", "--timeout", "1m30s"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<Duration>("timeout"),
+            Some(&Duration::from_secs(90))
+        );
+
+        let help = cmd.clone().render_help().to_string();
+        assert!(!help.contains("possible values"), "{help}");
+
+        let err = cmd
+            .try_get_matches_from(["test", "--timeout", "30"])
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains("invalid value '30' for '--timeout <timeout>': missing unit"),
+            "{message}"
+        );
+        assert!(message.contains("ns, us, ms"), "{message}");
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        #[derive(::schemars::JsonSchema, ::serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Config {
+            #[serde(with = "super::serde")]
+            #[schemars(schema_with = "super::serde::json_schema")]
+            timeout: Duration,
+            #[serde(with = "super::serde::millis")]
+            #[schemars(schema_with = "super::serde::millis::json_schema")]
+            interval: Duration,
+            #[serde(with = "super::serde_option")]
+            #[schemars(schema_with = "super::serde_option::json_schema")]
+            deadline: Option<Duration>,
         }
+
+        let schema = ::schemars::schema_for!(Config);
+        let properties = &schema.as_value()["properties"];
+
+        let timeout = &properties["timeout"];
+        assert_eq!(timeout["type"], serde_json::json!(["string", "number"]));
+        assert!(
+            timeout["pattern"]
+                .as_str()
+                .unwrap()
+                .contains("ms|s|m|h|d|w")
+        );
+        assert_eq!(timeout["examples"][0], "1h30m");
+
+        let pattern = ::regex::Regex::new(timeout["pattern"].as_str().unwrap()).unwrap();
+        let inputs = [
+            "0",
+            "-0",
+            "+0",
+            "-0s",
+            "-0h0.0m",
+            "1h30m",
+            "+.5s",
+            "1.5µs",
+            "90:00",
+            "1:30:00",
+            "01:02:03.450",
+            "2:03:04:05",
+            "2-03:04:05",
+            "2d 03:04:05",
+        ];
+        for input in inputs {
+            assert!(pattern.is_match(input), "input: {input}");
+            assert!(parse_duration(input).is_ok(), "input: {input}");
+        }
+        let inputs = [
+            "",
+            "-1s",
+            "-0s1ns",
+            "1",
+            "1x",
+            "s",
+            "1:60",
+            "1:60:00",
+            "1:24:00:00",
+            "2d03:04:05",
+            "1:2:3:4:5",
+            "-1:00",
+        ];
+        for input in inputs {
+            assert!(!pattern.is_match(input), "input: {input}");
+            assert!(parse_duration(input).is_err(), "input: {input}");
+        }
+        assert!(
+            properties["interval"]["description"]
+                .as_str()
+                .unwrap()
+                .ends_with("milliseconds")
+        );
+        assert_eq!(
+            properties["deadline"]["type"],
+            serde_json::json!(["string", "number", "null"])
+        );
+
+        let config: Config = serde_json::from_str(
+            r#"{"timeout": "1:30:00", "interval": "00:01.5", "deadline": "1d 02:00:00"}"#,
+        )
+        .unwrap();
+        assert_eq!(config.timeout, Duration::from_secs(5400));
+        assert_eq!(config.interval, Duration::from_millis(1500));
+        assert_eq!(config.deadline, Some(Duration::from_secs(93600)));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn chrono() {
+        use ::chrono::TimeDelta;
+
+        use super::chrono::{duration, parse_duration};
+
+        let tests = [
+            ("0s", TimeDelta::zero()),
+            ("-1h30m", TimeDelta::minutes(-90)),
+            ("-1.5s", TimeDelta::milliseconds(-1500)),
+            ("-1ns", TimeDelta::nanoseconds(-1)),
+            ("2562047h47m16.854775807s", TimeDelta::nanoseconds(i64::MAX)),
+            ("2562047788015h12m55.807s", TimeDelta::MAX),
+            ("-2562047788015h12m55.807s", TimeDelta::MIN),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(parse_duration(input).unwrap(), want, "input: {input}");
+            assert_eq!(duration(&want), input);
+        }
+
+        assert_eq!(
+            parse_duration("2562047788015h12m55.808s"),
+            Err(Error::InvalidDuration)
+        );
+        assert_eq!(parse_duration("1x"), Err(Error::UnknownUnit));
+    }
+
+    #[cfg(feature = "time")]
+    #[test]
+    fn time() {
+        use ::time::Duration;
+
+        use super::time::{duration, parse_duration};
+
+        let tests = [
+            ("0s", Duration::ZERO),
+            ("-1h30m", Duration::minutes(-90)),
+            ("-1.5s", Duration::milliseconds(-1500)),
+            ("168h", Duration::WEEK),
+            ("2562047788015215h30m7.999999999s", Duration::MAX),
+            ("-2562047788015215h30m8.999999999s", Duration::MIN),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(parse_duration(input).unwrap(), want, "input: {input}");
+            assert_eq!(duration(&want), input);
+        }
+
+        assert_eq!(
+            parse_duration("-18446744073709551615s"),
+            Err(Error::InvalidDuration)
+        );
+    }
+
+    #[cfg(all(feature = "serde", feature = "chrono", feature = "time"))]
+    #[test]
+    fn serde_signed() {
+        #[derive(Debug, PartialEq, ::serde::Deserialize, ::serde::Serialize)]
+        struct Config {
+            #[serde(with = "super::chrono::serde")]
+            offset: ::chrono::TimeDelta,
+            #[serde(with = "super::chrono::serde_option", default)]
+            skew: Option<::chrono::TimeDelta>,
+            #[serde(with = "super::time::serde")]
+            shift: ::time::Duration,
+            #[serde(with = "super::time::serde_option", default)]
+            drift: Option<::time::Duration>,
+        }
+
+        let got: Config =
+            serde_json::from_str(r#"{"offset": "-1h", "skew": -30, "shift": -1.5, "drift": null}"#)
+                .unwrap();
+        let want = Config {
+            offset: ::chrono::TimeDelta::hours(-1),
+            skew: Some(::chrono::TimeDelta::seconds(-30)),
+            shift: ::time::Duration::milliseconds(-1500),
+            drift: None,
+        };
+        assert_eq!(got, want);
+
+        let json = serde_json::to_string(&want).unwrap();
+        assert_eq!(
+            json,
+            r#"{"offset":"-1h","skew":"-30s","shift":"-1.5s","drift":null}"#
+        );
+        assert_eq!(serde_json::from_str::<Config>(&json).unwrap(), want);
+
+        let input = r#"{"offset": "-1h", "shift": "10000000000000000000s"}"#;
+        assert!(serde_json::from_str::<Config>(input).is_err());
     }
 }
@@ -0,0 +1,191 @@
+// Read settings like APP_CACHE_SIZE=512MiB from the environment
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::hash::BuildHasher;
+
+/// Source is a set of variables, like the process environment.
+///
+/// It is implemented for maps and slices of pairs, so settings can be tested
+/// without touching the real environment.
+pub trait Source {
+    /// get returns the value of the variable `key`, or None if it is not set.
+    fn get(&self, key: &str) -> Option<String>;
+}
+
+/// Env is the environment of the current process.
+///
+/// Values that are not valid unicode are converted lossily, so they fail to
+/// parse instead of looking unset.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Env;
+
+impl Source for Env {
+    fn get(&self, key: &str) -> Option<String> {
+        std::env::var_os(key).map(|value| value.to_string_lossy().into_owned())
+    }
+}
+
+impl<S: BuildHasher> Source for HashMap<String, String, S> {
+    fn get(&self, key: &str) -> Option<String> {
+        HashMap::get(self, key).cloned()
+    }
+}
+
+impl<S: BuildHasher> Source for HashMap<&str, &str, S> {
+    fn get(&self, key: &str) -> Option<String> {
+        HashMap::get(self, key).map(|value| value.to_string())
+    }
+}
+
+impl Source for BTreeMap<String, String> {
+    fn get(&self, key: &str) -> Option<String> {
+        BTreeMap::get(self, key).cloned()
+    }
+}
+
+impl Source for BTreeMap<&str, &str> {
+    fn get(&self, key: &str) -> Option<String> {
+        BTreeMap::get(self, key).map(|value| value.to_string())
+    }
+}
+
+impl Source for [(&str, &str)] {
+    fn get(&self, key: &str) -> Option<String> {
+        self.iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| value.to_string())
+    }
+}
+
+impl<const N: usize> Source for [(&str, &str); N] {
+    fn get(&self, key: &str) -> Option<String> {
+        Source::get(&self[..], key)
+    }
+}
+
+impl<T: Source + ?Sized> Source for &T {
+    fn get(&self, key: &str) -> Option<String> {
+        (**self).get(key)
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+#[non_exhaustive]
+pub enum Error {
+    Missing {
+        key: String,
+    },
+    Invalid {
+        key: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Missing { key } => write!(f, "{} is not set", key),
+            Error::Invalid { key, value, reason } => {
+                write!(f, "invalid {}=\"{}\": {}", key, value, reason)
+            }
+        }
+    }
+}
+
+/// parse reads the variable `key` from `source` and parses it with `parse`.
+/// An empty variable counts as unset.
+///
+/// parse(&[("N", "x")], "N", |v| v.parse::<u8>()) -> Err(invalid N="x": invalid digit found in string)
+///
+/// # Errors
+///
+/// Return `Error::Invalid` naming the variable and its value if `parse` fails.
+pub fn parse<S, T, E>(
+    source: &S,
+    key: &str,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<Option<T>, Error>
+where
+    S: Source + ?Sized,
+    E: Display,
+{
+    let value = match source.get(key) {
+        Some(value) if !value.is_empty() => value,
+        _ => return Ok(None),
+    };
+
+    match parse(&value) {
+        Ok(v) => Ok(Some(v)),
+        Err(err) => Err(Error::Invalid {
+            key: key.to_string(),
+            reason: err.to_string(),
+            value,
+        }),
+    }
+}
+
+/// require is like `parse`, but an unset variable is an error.
+///
+/// # Errors
+///
+/// Return `Error::Missing` if the variable is not set, or `Error::Invalid` if
+/// `parse` fails.
+pub fn require<S, T, E>(
+    source: &S,
+    key: &str,
+    parse: impl FnOnce(&str) -> Result<T, E>,
+) -> Result<T, Error>
+where
+    S: Source + ?Sized,
+    E: Display,
+{
+    self::parse(source, key, parse)?.ok_or_else(|| Error::Missing {
+        key: key.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sources() {
+        let pairs = [("A", "1"), ("B", "")];
+        let hash: HashMap<&str, &str> = pairs.into_iter().collect();
+        let tree: BTreeMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+
+        let sources: [&dyn Source; 4] = [&pairs, &&pairs[..], &hash, &tree];
+        for source in sources {
+            assert_eq!(source.get("A").as_deref(), Some("1"));
+            assert_eq!(source.get("B").as_deref(), Some(""));
+            assert_eq!(source.get("C"), None);
+        }
+
+        assert_eq!(Env.get("HUMANIZE_SURELY_NOT_SET"), None);
+    }
+
+    #[test]
+    fn errors() {
+        let source = [("N", "x"), ("EMPTY", "")];
+        let number = |v: &str| v.parse::<u8>();
+
+        assert_eq!(parse(&source, "EMPTY", number), Ok(None));
+        assert_eq!(parse(&source, "UNSET", number), Ok(None));
+
+        let err = parse(&source, "N", number).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid N=\"x\": invalid digit found in string"
+        );
+
+        let err = require(&source, "EMPTY", number).unwrap_err();
+        assert_eq!(err.to_string(), "EMPTY is not set");
+    }
+}
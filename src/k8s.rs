@@ -0,0 +1,714 @@
+// Port from Kubernetes' apimachinery resource.Quantity
+//
+// Sizes from `bytes::parse_bytes` convert with `Quantity::from_bytes`, and back
+// with `Quantity::as_bytes` for `bytes::ibytes`. Counts like cores or pods
+// convert from and to `i64` and `u64` with `From` and `TryFrom`.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+
+const NANO: u128 = 1_000_000_000;
+
+const DECIMAL_SUFFIXES: [(&str, i32); 10] = [
+    ("n", -9),
+    ("u", -6),
+    ("m", -3),
+    ("", 0),
+    ("k", 3),
+    ("M", 6),
+    ("G", 9),
+    ("T", 12),
+    ("P", 15),
+    ("E", 18),
+];
+
+const BINARY_SUFFIXES: [(&str, u32); 6] = [
+    ("Ki", 1),
+    ("Mi", 2),
+    ("Gi", 3),
+    ("Ti", 4),
+    ("Pi", 5),
+    ("Ei", 6),
+];
+
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidNumber,
+    UnknownSuffix,
+    TooLarge,
+    OutOfRange,
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let msg = match self {
+            Error::InvalidNumber => "invalid number",
+            Error::UnknownSuffix => "unknown suffix",
+            Error::TooLarge => "quantity too large",
+            Error::OutOfRange => "quantity out of range",
+        };
+
+        write!(f, "{}", msg)
+    }
+}
+
+/// Format is the serialization format of a `Quantity`, it is picked from the
+/// suffix of the parsed input and preserved when formatting.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum Format {
+    /// e.g., 12Mi = 12 * 2^20
+    BinarySI,
+    /// e.g., 12M = 12 * 10^6, 100m = 0.1
+    DecimalSI,
+    /// e.g., 12e6 = 12 * 10^6
+    DecimalExponent,
+}
+
+/// Quantity is a fixed-point representation of a Kubernetes resource quantity,
+/// with nano precision.
+///
+/// Values which cannot be represented exactly with nano precision are rounded up,
+/// the same as Kubernetes does.
+#[derive(Debug, Copy, Clone)]
+pub struct Quantity {
+    nanos: i128,
+    format: Format,
+}
+
+impl Quantity {
+    #[must_use]
+    pub const fn new(value: i64, format: Format) -> Self {
+        Self {
+            nanos: value as i128 * NANO as i128,
+            format,
+        }
+    }
+
+    #[must_use]
+    pub const fn from_milli(milli: i64, format: Format) -> Self {
+        Self {
+            nanos: milli as i128 * 1_000_000,
+            format,
+        }
+    }
+
+    /// from_bytes creates a `BinarySI` quantity, e.g. for memory or storage requests.
+    #[must_use]
+    pub const fn from_bytes(bytes: usize) -> Self {
+        Self {
+            nanos: bytes as i128 * NANO as i128,
+            format: Format::BinarySI,
+        }
+    }
+
+    #[must_use]
+    pub const fn format(&self) -> Format {
+        self.format
+    }
+
+    #[must_use]
+    pub const fn is_zero(&self) -> bool {
+        self.nanos == 0
+    }
+
+    /// value returns the integer value of the quantity, rounded up.
+    /// Return None if the value does not fit in an i64.
+    #[must_use]
+    pub fn value(&self) -> Option<i64> {
+        i64::try_from(div_ceil(self.nanos, NANO as i128)).ok()
+    }
+
+    /// milli_value returns the value of the quantity in thousandths, rounded up.
+    /// Return None if the value does not fit in an i64.
+    #[must_use]
+    pub fn milli_value(&self) -> Option<i64> {
+        i64::try_from(div_ceil(self.nanos, 1_000_000)).ok()
+    }
+
+    /// as_bytes returns the number of bytes, rounded up.
+    /// Return None if the quantity is negative or does not fit in an usize.
+    #[must_use]
+    pub fn as_bytes(&self) -> Option<usize> {
+        usize::try_from(div_ceil(self.nanos, NANO as i128)).ok()
+    }
+}
+
+/// Counts are `DecimalSI` quantities, e.g. 2 cores.
+impl From<i64> for Quantity {
+    fn from(count: i64) -> Self {
+        Self::new(count, Format::DecimalSI)
+    }
+}
+
+impl From<u64> for Quantity {
+    fn from(count: u64) -> Self {
+        Self {
+            nanos: count as i128 * NANO as i128,
+            format: Format::DecimalSI,
+        }
+    }
+}
+
+/// Converts to a count rounded up like `Quantity::value`, "100m" is 1.
+impl TryFrom<Quantity> for i64 {
+    type Error = Error;
+
+    fn try_from(q: Quantity) -> Result<Self, Self::Error> {
+        q.value().ok_or(Error::OutOfRange)
+    }
+}
+
+/// Converts to a count rounded up like `Quantity::value`, negative quantities
+/// are out of range.
+impl TryFrom<Quantity> for u64 {
+    type Error = Error;
+
+    fn try_from(q: Quantity) -> Result<Self, Self::Error> {
+        u64::try_from(div_ceil(q.nanos, NANO as i128)).map_err(|_| Error::OutOfRange)
+    }
+}
+
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        self.nanos == other.nanos
+    }
+}
+
+impl Eq for Quantity {}
+
+impl PartialOrd for Quantity {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Quantity {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.nanos.cmp(&other.nanos)
+    }
+}
+
+/// Display writes the canonical form of the quantity, that is
+/// - no precision is lost
+/// - no fractional digits are emitted
+/// - the exponent (or suffix) is as large as possible
+///
+/// For example, "1.5" is written as "1500m" and "1.5Gi" as "1536Mi".
+impl Display for Quantity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.nanos == 0 {
+            return f.write_str("0");
+        }
+
+        let sign = if self.nanos < 0 { "-" } else { "" };
+        let magnitude = self.nanos.unsigned_abs();
+
+        // Values below 1Ki, or with a fraction, cannot be written with
+        // binary suffixes, use decimal ones instead.
+        if self.format == Format::BinarySI
+            && magnitude >= 1024 * NANO
+            && magnitude.is_multiple_of(NANO)
+        {
+            let mut mantissa = magnitude / NANO;
+            let mut suffix = "";
+            for (unit, _) in BINARY_SUFFIXES {
+                if !mantissa.is_multiple_of(1024) {
+                    break;
+                }
+
+                mantissa /= 1024;
+                suffix = unit;
+            }
+
+            return write!(f, "{}{}{}", sign, mantissa, suffix);
+        }
+
+        // Move all factors of 10 into the exponent, and then make sure the
+        // exponent is a multiple of 3.
+        let mut mantissa = magnitude;
+        let mut exponent = -9;
+        while mantissa.is_multiple_of(10) {
+            mantissa /= 10;
+            exponent += 1;
+        }
+        while exponent % 3 != 0 {
+            mantissa *= 10;
+            exponent -= 1;
+        }
+
+        let suffix = DECIMAL_SUFFIXES
+            .iter()
+            .find(|(_, exp)| *exp == exponent)
+            .map(|(unit, _)| *unit);
+
+        match (self.format, suffix) {
+            (Format::DecimalExponent, _) | (_, None) if exponent != 0 => {
+                write!(f, "{}{}e{}", sign, mantissa, exponent)
+            }
+            (_, suffix) => write!(f, "{}{}{}", sign, mantissa, suffix.unwrap_or_default()),
+        }
+    }
+}
+
+/// `parse_quantity` parses a Kubernetes quantity, such as "100m", "1.5Gi" or "129e6".
+///
+/// The grammar is
+///
+/// ```text
+/// <quantity>        ::= <sign>?<number><suffix>
+/// <number>          ::= <digits> | <digits>.<digits> | <digits>. | .<digits>
+/// <suffix>          ::= <binarySI> | <decimalExponent> | <decimalSI>
+/// <binarySI>        ::= Ki | Mi | Gi | Ti | Pi | Ei
+/// <decimalSI>       ::= n | u | m | "" | k | M | G | T | P | E
+/// <decimalExponent> ::= "e" <sign>?<digits> | "E" <sign>?<digits>
+/// ```
+///
+/// # Errors
+///
+/// Return `Error` if the input is not valid, or the value is too large.
+pub fn parse_quantity(input: &str) -> Result<Quantity, Error> {
+    let mut s = input.as_bytes();
+
+    // Consume [-+]?
+    let neg = s.first() == Some(&b'-');
+    if let Some(b'-' | b'+') = s.first() {
+        s = &s[1..];
+    }
+
+    // Consume [0-9]*(\.[0-9]*)?
+    let int_len = s.iter().take_while(|c| c.is_ascii_digit()).count();
+    let (int, remain) = s.split_at(int_len);
+    s = remain;
+    let mut frac: &[u8] = &[];
+    if let Some(b'.') = s.first() {
+        let frac_len = s[1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        frac = &s[1..=frac_len];
+        s = &s[1 + frac_len..];
+    }
+    if int.is_empty() && frac.is_empty() {
+        return Err(Error::InvalidNumber);
+    }
+
+    let (exponent, binary, format) = parse_suffix(s)?;
+
+    let magnitude = scale(int, frac, exponent, binary)?;
+    let nanos = i128::try_from(magnitude).map_err(|_| Error::TooLarge)?;
+
+    Ok(Quantity {
+        nanos: if neg { -nanos } else { nanos },
+        format,
+    })
+}
+
+/// parse_suffix returns the decimal exponent, the binary exponent (in powers
+/// of 1024) and the format of the suffix.
+fn parse_suffix(suffix: &[u8]) -> Result<(i32, u32, Format), Error> {
+    if let [b'e' | b'E', exp @ ..] = suffix {
+        let digits = match exp {
+            [b'-' | b'+', digits @ ..] => digits,
+            digits => digits,
+        };
+
+        if !digits.is_empty() && digits.iter().all(u8::is_ascii_digit) {
+            // Anything larger than a few digits overflows anyway
+            let exponent = std::str::from_utf8(exp)
+                .ok()
+                .and_then(|exp| exp.parse::<i32>().ok())
+                .ok_or(Error::TooLarge)?;
+
+            return Ok((exponent, 0, Format::DecimalExponent));
+        }
+    }
+
+    if let Some((_, exp)) = BINARY_SUFFIXES
+        .iter()
+        .find(|(unit, _)| unit.as_bytes() == suffix)
+    {
+        return Ok((0, *exp, Format::BinarySI));
+    }
+
+    DECIMAL_SUFFIXES
+        .iter()
+        .find(|(unit, _)| unit.as_bytes() == suffix)
+        .map(|(_, exp)| (*exp, 0, Format::DecimalSI))
+        .ok_or(Error::UnknownSuffix)
+}
+
+/// scale returns `int.frac * 10^exponent * 1024^binary` in nanos, rounded up.
+fn scale(int: &[u8], frac: &[u8], exponent: i32, binary: u32) -> Result<u128, Error> {
+    // Significant digits only, leading zeros of the integer part and trailing
+    // zeros of the fraction part don't matter.
+    let mut digits = int
+        .iter()
+        .chain(frac.iter())
+        .skip_while(|c| **c == b'0')
+        .copied()
+        .collect::<Vec<_>>();
+    let mut exponent = i64::from(exponent) - frac.len() as i64 + 9;
+    while let Some(b'0') = digits.last() {
+        digits.pop();
+        exponent += 1;
+    }
+    if digits.is_empty() {
+        return Ok(0);
+    }
+
+    // Drop digits which are not representable anyway, the value is rounded
+    // up later.
+    let mut truncated = false;
+    if digits.len() > 36 {
+        exponent += (digits.len() - 36) as i64;
+        digits.truncate(36);
+        truncated = true;
+    }
+    let mantissa = digits
+        .iter()
+        .fold(0u128, |acc, c| acc * 10 + u128::from(c - b'0'));
+    let mantissa = mantissa
+        .checked_mul(1024u128.pow(binary))
+        .ok_or(Error::TooLarge)?;
+
+    if exponent >= 0 {
+        let pow = u32::try_from(exponent)
+            .ok()
+            .and_then(|exp| 10u128.checked_pow(exp))
+            .ok_or(Error::TooLarge)?;
+        return mantissa.checked_mul(pow).ok_or(Error::TooLarge);
+    }
+
+    match u32::try_from(-exponent)
+        .ok()
+        .and_then(|exp| 10u128.checked_pow(exp))
+    {
+        Some(pow) => {
+            let value = mantissa / pow;
+            if truncated || !mantissa.is_multiple_of(pow) {
+                Ok(value + 1)
+            } else {
+                Ok(value)
+            }
+        }
+        // Smaller than one nano, round up
+        None => Ok(1),
+    }
+}
+
+fn div_ceil(value: i128, divisor: i128) -> i128 {
+    let quotient = value / divisor;
+    if value % divisor > 0 {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_quantity {
+    use std::fmt::Formatter;
+
+    use serde_core::de::{Error, Unexpected, Visitor};
+    use serde_core::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{Quantity, parse_quantity};
+
+    /// Accepts a quantity string, or a number like `cpu: 2` in a manifest.
+    /// Floats are parsed from their decimal form, as Kubernetes does.
+    struct QuantityVisitor;
+
+    impl<'de> Visitor<'de> for QuantityVisitor {
+        type Value = Quantity;
+
+        fn expecting(&self, f: &mut Formatter) -> std::fmt::Result {
+            write!(f, "a number or a quantity string like \"500m\" or \"1Gi\"")
+        }
+
+        fn visit_u64<E: Error>(self, v: u64) -> Result<Self::Value, E> {
+            Ok(Quantity::from(v))
+        }
+
+        fn visit_i64<E: Error>(self, v: i64) -> Result<Self::Value, E> {
+            Ok(Quantity::from(v))
+        }
+
+        fn visit_f64<E: Error>(self, v: f64) -> Result<Self::Value, E> {
+            if !v.is_finite() {
+                return Err(E::invalid_value(Unexpected::Float(v), &self));
+            }
+            parse_quantity(&v.to_string()).map_err(Error::custom)
+        }
+
+        fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
+            parse_quantity(v).map_err(Error::custom)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Quantity {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_any(QuantityVisitor)
+            } else {
+                deserializer.deserialize_str(QuantityVisitor)
+            }
+        }
+    }
+
+    impl Serialize for Quantity {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            s.collect_str(self)
+        }
+    }
+
+    #[cfg(feature = "schemars")]
+    impl schemars::JsonSchema for Quantity {
+        fn schema_name() -> std::borrow::Cow<'static, str> {
+            "Quantity".into()
+        }
+
+        fn schema_id() -> std::borrow::Cow<'static, str> {
+            "humanize::k8s::Quantity".into()
+        }
+
+        // The same pattern as the Kubernetes OpenAPI schema
+        fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+            schemars::json_schema!({
+                "description": "A Kubernetes resource quantity like \"500m\" or \"1Gi\", or a number",
+                "type": ["string", "number"],
+                "pattern": r"^(\+|-)?(([0-9]+(\.[0-9]*)?)|(\.[0-9]+))(([KMGTPE]i)|[numkMGTPE]|([eE](\+|-)?(([0-9]+(\.[0-9]*)?)|(\.[0-9]+))))?$",
+                "examples": ["500m", "1Gi", "1.5", 2],
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical() {
+        let tests = [
+            ("0", "0"),
+            ("0Ki", "0"),
+            ("-0", "0"),
+            ("1", "1"),
+            ("+1", "1"),
+            ("1000", "1k"),
+            ("1200", "1200"),
+            ("1.5", "1500m"),
+            (".5", "500m"),
+            ("5.", "5"),
+            ("0.1", "100m"),
+            ("100m", "100m"),
+            ("1000m", "1"),
+            ("1500m", "1500m"),
+            ("1n", "1n"),
+            ("10u", "10u"),
+            ("1k", "1k"),
+            ("1M", "1M"),
+            ("1G", "1G"),
+            ("1T", "1T"),
+            ("1P", "1P"),
+            ("1E", "1E"),
+            ("1000E", "1e21"),
+            ("-1.5", "-1500m"),
+            // binary
+            ("1Ki", "1Ki"),
+            ("1024", "1024"),
+            ("1024Ki", "1Mi"),
+            ("1.5Gi", "1536Mi"),
+            ("1.5Ki", "1536"),
+            ("0.5Ki", "512"),
+            ("-1Ki", "-1Ki"),
+            ("1.0001Ki", "1024102400u"),
+            ("128974848", "128974848"),
+            ("123Mi", "123Mi"),
+            ("1Ei", "1Ei"),
+            ("1024Ei", "1024Ei"),
+            // decimal exponent
+            ("1e3", "1e3"),
+            ("1E3", "1e3"),
+            ("129e6", "129e6"),
+            ("1e0", "1"),
+            ("1.5e3", "1500"),
+            ("1e-3", "1e-3"),
+            ("12e+6", "12e6"),
+            ("100e1", "1e3"),
+            ("1e1", "10"),
+            // precision
+            ("0.0000000001", "1n"),
+            ("1.0000000001", "1000000001n"),
+            ("-0.0000000001", "-1n"),
+            ("1e-20", "1e-9"),
+            (
+                "0.100000000000000000000000000000000000000000001",
+                "100000001n",
+            ),
+        ];
+
+        for (input, want) in tests {
+            let q = parse_quantity(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+            assert_eq!(q.to_string(), want, "input: {input}");
+
+            // canonical form parses to the same value
+            assert_eq!(parse_quantity(want).unwrap(), q, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let tests = [
+            ("", Error::InvalidNumber),
+            (".", Error::InvalidNumber),
+            ("-", Error::InvalidNumber),
+            ("Ki", Error::InvalidNumber),
+            ("1 Ki", Error::UnknownSuffix),
+            ("1KB", Error::UnknownSuffix),
+            ("1ki", Error::UnknownSuffix),
+            ("1K", Error::UnknownSuffix),
+            ("1e", Error::UnknownSuffix),
+            ("1e1.5", Error::UnknownSuffix),
+            ("1.2.3", Error::UnknownSuffix),
+            ("1e99", Error::TooLarge),
+            ("1e99999999999", Error::TooLarge),
+            ("1000000000000000000000000000000", Error::TooLarge),
+        ];
+
+        for (input, want) in tests {
+            assert_eq!(parse_quantity(input).unwrap_err(), want, "input: {input}");
+        }
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(parse_quantity("1Ki").unwrap().format(), Format::BinarySI);
+        assert_eq!(parse_quantity("1").unwrap().format(), Format::DecimalSI);
+        assert_eq!(parse_quantity("1m").unwrap().format(), Format::DecimalSI);
+        assert_eq!(
+            parse_quantity("1e3").unwrap().format(),
+            Format::DecimalExponent
+        );
+
+        assert_eq!(Quantity::new(1000, Format::DecimalSI).to_string(), "1k");
+        assert_eq!(
+            Quantity::new(1000, Format::DecimalExponent).to_string(),
+            "1e3"
+        );
+        assert_eq!(Quantity::new(2048, Format::BinarySI).to_string(), "2Ki");
+        assert_eq!(
+            Quantity::from_milli(1500, Format::DecimalSI).to_string(),
+            "1500m"
+        );
+        assert_eq!(Quantity::from_bytes(64 * 1024 * 1024).to_string(), "64Mi");
+    }
+
+    #[test]
+    fn values() {
+        let q = parse_quantity("1.5Ki").unwrap();
+        assert_eq!(q.as_bytes(), Some(1536));
+        assert_eq!(q.value(), Some(1536));
+        assert_eq!(q.milli_value(), Some(1536000));
+
+        let q = parse_quantity("100m").unwrap();
+        assert_eq!(q.value(), Some(1));
+        assert_eq!(q.milli_value(), Some(100));
+
+        let q = parse_quantity("-100m").unwrap();
+        assert_eq!(q.value(), Some(0));
+        assert_eq!(q.as_bytes(), Some(0));
+        assert_eq!(parse_quantity("-1").unwrap().as_bytes(), None);
+        assert_eq!(parse_quantity("1e20").unwrap().value(), None);
+
+        assert!(parse_quantity("1Ki").unwrap() == parse_quantity("1024").unwrap());
+        assert!(parse_quantity("1Ki").unwrap() > parse_quantity("1k").unwrap());
+        assert!(parse_quantity("0").unwrap().is_zero());
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes_interop() {
+        use crate::bytes::parse_bytes;
+
+        for input in ["42Mi", "1Gi", "512Ki"] {
+            let size = parse_bytes(input).unwrap();
+            assert_eq!(Quantity::from_bytes(size).to_string(), input);
+            assert_eq!(parse_quantity(input).unwrap().as_bytes(), Some(size));
+        }
+
+        let q = parse_quantity("1536Mi").unwrap();
+        assert_eq!(q.as_bytes().map(crate::bytes::ibytes).unwrap(), "1.5GiB");
+    }
+
+    #[test]
+    fn counts() {
+        assert_eq!(Quantity::from(3i64).to_string(), "3");
+        assert_eq!(Quantity::from(-2i64).to_string(), "-2");
+        assert_eq!(Quantity::from(4000u64).to_string(), "4k");
+        assert_eq!(Quantity::from(u64::MAX).format(), Format::DecimalSI);
+        assert_eq!(u64::try_from(Quantity::from(u64::MAX)), Ok(u64::MAX));
+
+        assert_eq!(i64::try_from(parse_quantity("250m").unwrap()), Ok(1));
+        assert_eq!(i64::try_from(parse_quantity("-1.5").unwrap()), Ok(-1));
+        assert_eq!(u64::try_from(parse_quantity("2k").unwrap()), Ok(2000));
+        assert_eq!(
+            i64::try_from(parse_quantity("1e20").unwrap()),
+            Err(Error::OutOfRange)
+        );
+        assert_eq!(
+            u64::try_from(parse_quantity("-1").unwrap()),
+            Err(Error::OutOfRange)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        #[derive(Debug, PartialEq, ::serde::Deserialize)]
+        struct Resources {
+            cpu: Quantity,
+            memory: Quantity,
+        }
+
+        let want = Resources {
+            cpu: parse_quantity("2").unwrap(),
+            memory: parse_quantity("1Gi").unwrap(),
+        };
+        let got: Resources = serde_json::from_str(r#"{"cpu": 2, "memory": 1073741824}"#).unwrap();
+        assert_eq!(got, want);
+        let got: Resources = serde_yaml_ng::from_str("cpu: 2\nmemory: 1073741824\n").unwrap();
+        assert_eq!(got, want);
+
+        let want = Resources {
+            cpu: parse_quantity("500m").unwrap(),
+            memory: parse_quantity("-1").unwrap(),
+        };
+        let got: Resources = serde_json::from_str(r#"{"cpu": 0.5, "memory": -1}"#).unwrap();
+        assert_eq!(got, want);
+        let got: Resources = serde_yaml_ng::from_str("cpu: 500m\nmemory: -1\n").unwrap();
+        assert_eq!(got, want);
+        assert_eq!(got.cpu.to_string(), "500m");
+
+        let got: Quantity = serde_yaml_ng::from_str("1.5Gi").unwrap();
+        assert_eq!(got.to_string(), "1536Mi");
+        for input in ["true", r#""1.5Xi""#, "[1]"] {
+            assert!(
+                serde_json::from_str::<Quantity>(input).is_err(),
+                "input: {input}"
+            );
+        }
+        assert!(serde_yaml_ng::from_str::<Quantity>(".nan").is_err());
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn json_schema() {
+        let schema = schemars::schema_for!(Quantity);
+        assert_eq!(
+            schema.as_value()["type"],
+            serde_json::json!(["string", "number"])
+        );
+        assert_eq!(schema.as_value()["title"], "Quantity");
+    }
+}
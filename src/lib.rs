@@ -3,3 +3,24 @@ pub mod bytes;
 
 #[cfg(feature = "duration")]
 pub mod duration;
+
+#[cfg(feature = "percent")]
+pub mod percent;
+
+#[cfg(feature = "k8s")]
+pub mod k8s;
+
+#[cfg(feature = "env")]
+pub mod env;
+
+#[cfg(feature = "locale")]
+pub mod locale;
+
+#[cfg(feature = "backoff")]
+pub mod backoff;
+
+#[cfg(feature = "period")]
+pub mod period;
+
+#[cfg(all(feature = "clap", any(feature = "bytes", feature = "duration")))]
+mod cli;
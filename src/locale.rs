@@ -0,0 +1,973 @@
+// Localized formatting and parsing of sizes and durations
+
+use std::fmt::{Display, Formatter};
+
+use std::time::Duration;
+
+/// Unit is a size or time unit which a `Locale` names.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Unit {
+    Byte,
+    Kilobyte,
+    Megabyte,
+    Gigabyte,
+    Terabyte,
+    Petabyte,
+    Exabyte,
+    Kibibyte,
+    Mebibyte,
+    Gibibyte,
+    Tebibyte,
+    Pebibyte,
+    Exbibyte,
+    Nanosecond,
+    Microsecond,
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+}
+
+const SI_UNITS: [Unit; 7] = [
+    Unit::Byte,
+    Unit::Kilobyte,
+    Unit::Megabyte,
+    Unit::Gigabyte,
+    Unit::Terabyte,
+    Unit::Petabyte,
+    Unit::Exabyte,
+];
+
+const IEC_UNITS: [Unit; 7] = [
+    Unit::Byte,
+    Unit::Kibibyte,
+    Unit::Mebibyte,
+    Unit::Gibibyte,
+    Unit::Tebibyte,
+    Unit::Pebibyte,
+    Unit::Exbibyte,
+];
+
+/// Plural is the plural category of a number, only the categories needed by
+/// the built-in locales are supported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Plural {
+    One,
+    Other,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidNumber { number: String },
+    AmbiguousNumber { number: String },
+    UnknownUnit { unit: String },
+    AmbiguousUnit { unit: String },
+    TooLarge { input: String },
+    Duration(crate::duration::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidNumber { number } => write!(f, "invalid number \"{}\"", number),
+            Error::AmbiguousNumber { number } => write!(
+                f,
+                "ambiguous number \"{}\", the separator could be decimal or grouping",
+                number
+            ),
+            Error::UnknownUnit { unit } => write!(f, "unknown unit \"{}\"", unit),
+            Error::AmbiguousUnit { unit } => write!(f, "ambiguous unit \"{}\"", unit),
+            Error::TooLarge { input } => write!(f, "too large \"{}\"", input),
+            Error::Duration(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Locale names units and formats numbers for a language.
+pub trait Locale {
+    /// decimal_separator separates the integer and fraction digits, e.g. ','.
+    fn decimal_separator(&self) -> char;
+
+    /// separator goes between numbers and unit symbols, and between the parts
+    /// of a duration, in the short forms.
+    fn separator(&self) -> &str;
+
+    /// symbol returns the short name of a unit, e.g. "Go".
+    fn symbol(&self, unit: Unit) -> &str;
+
+    /// name returns the long name of a unit, e.g. "gigaoctets".
+    fn name(&self, unit: Unit, plural: Plural) -> &str;
+
+    /// plural returns the plural category of a number.
+    fn plural(&self, n: f64) -> Plural;
+
+    /// grouping_separator separates groups of thousands when parsing, e.g.
+    /// '.' in "1.500,5". A space also accepts no-break spaces.
+    fn grouping_separator(&self) -> Option<char> {
+        None
+    }
+}
+
+/// Builtin is one of the locales shipped with this crate.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Builtin {
+    En,
+    De,
+    Fr,
+    Ja,
+}
+
+impl Builtin {
+    /// from_tag returns the locale of a language tag like "de", "fr-CA" or
+    /// "ja_JP.UTF-8", only the language is considered.
+    #[must_use]
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_', '.']).next().unwrap_or_default();
+        [Self::En, Self::De, Self::Fr, Self::Ja]
+            .into_iter()
+            .find(|locale| language.eq_ignore_ascii_case(locale.table().language))
+    }
+
+    fn table(self) -> &'static Table {
+        match self {
+            Builtin::En => &EN,
+            Builtin::De => &DE,
+            Builtin::Fr => &FR,
+            Builtin::Ja => &JA,
+        }
+    }
+}
+
+impl Locale for Builtin {
+    fn decimal_separator(&self) -> char {
+        self.table().decimal
+    }
+
+    fn separator(&self) -> &str {
+        self.table().separator
+    }
+
+    fn symbol(&self, unit: Unit) -> &str {
+        self.table().symbols[unit as usize]
+    }
+
+    fn name(&self, unit: Unit, plural: Plural) -> &str {
+        let (one, other) = self.table().names[unit as usize];
+        match plural {
+            Plural::One => one,
+            Plural::Other => other,
+        }
+    }
+
+    fn plural(&self, n: f64) -> Plural {
+        (self.table().plural)(n)
+    }
+
+    fn grouping_separator(&self) -> Option<char> {
+        Some(self.table().grouping)
+    }
+}
+
+struct Table {
+    language: &'static str,
+    decimal: char,
+    grouping: char,
+    separator: &'static str,
+    symbols: [&'static str; 21],
+    names: [(&'static str, &'static str); 21],
+    plural: fn(f64) -> Plural,
+}
+
+// 1 is singular
+fn plural_one(n: f64) -> Plural {
+    if n == 1.0 { Plural::One } else { Plural::Other }
+}
+
+// 0 and 1, fractions included, are singular
+fn plural_french(n: f64) -> Plural {
+    if (0.0..2.0).contains(&n.abs()) {
+        Plural::One
+    } else {
+        Plural::Other
+    }
+}
+
+fn plural_none(_: f64) -> Plural {
+    Plural::Other
+}
+
+const EN: Table = Table {
+    language: "en",
+    decimal: '.',
+    grouping: ',',
+    separator: "",
+    symbols: [
+        "B", "kB", "MB", "GB", "TB", "PB", "EB", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ns",
+        "us", "ms", "s", "m", "h", "d", "w",
+    ],
+    names: [
+        ("byte", "bytes"),
+        ("kilobyte", "kilobytes"),
+        ("megabyte", "megabytes"),
+        ("gigabyte", "gigabytes"),
+        ("terabyte", "terabytes"),
+        ("petabyte", "petabytes"),
+        ("exabyte", "exabytes"),
+        ("kibibyte", "kibibytes"),
+        ("mebibyte", "mebibytes"),
+        ("gibibyte", "gibibytes"),
+        ("tebibyte", "tebibytes"),
+        ("pebibyte", "pebibytes"),
+        ("exbibyte", "exbibytes"),
+        ("nanosecond", "nanoseconds"),
+        ("microsecond", "microseconds"),
+        ("millisecond", "milliseconds"),
+        ("second", "seconds"),
+        ("minute", "minutes"),
+        ("hour", "hours"),
+        ("day", "days"),
+        ("week", "weeks"),
+    ],
+    plural: plural_one,
+};
+
+const DE: Table = Table {
+    language: "de",
+    decimal: ',',
+    grouping: '.',
+    separator: " ",
+    symbols: [
+        "B", "kB", "MB", "GB", "TB", "PB", "EB", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ns",
+        "µs", "ms", "Sek.", "Min.", "Std.", "T.", "Wo.",
+    ],
+    names: [
+        ("Byte", "Byte"),
+        ("Kilobyte", "Kilobyte"),
+        ("Megabyte", "Megabyte"),
+        ("Gigabyte", "Gigabyte"),
+        ("Terabyte", "Terabyte"),
+        ("Petabyte", "Petabyte"),
+        ("Exabyte", "Exabyte"),
+        ("Kibibyte", "Kibibyte"),
+        ("Mebibyte", "Mebibyte"),
+        ("Gibibyte", "Gibibyte"),
+        ("Tebibyte", "Tebibyte"),
+        ("Pebibyte", "Pebibyte"),
+        ("Exbibyte", "Exbibyte"),
+        ("Nanosekunde", "Nanosekunden"),
+        ("Mikrosekunde", "Mikrosekunden"),
+        ("Millisekunde", "Millisekunden"),
+        ("Sekunde", "Sekunden"),
+        ("Minute", "Minuten"),
+        ("Stunde", "Stunden"),
+        ("Tag", "Tage"),
+        ("Woche", "Wochen"),
+    ],
+    plural: plural_one,
+};
+
+const FR: Table = Table {
+    language: "fr",
+    decimal: ',',
+    grouping: ' ',
+    separator: " ",
+    symbols: [
+        "o", "ko", "Mo", "Go", "To", "Po", "Eo", "Kio", "Mio", "Gio", "Tio", "Pio", "Eio", "ns",
+        "µs", "ms", "s", "min", "h", "j", "sem.",
+    ],
+    names: [
+        ("octet", "octets"),
+        ("kilooctet", "kilooctets"),
+        ("mégaoctet", "mégaoctets"),
+        ("gigaoctet", "gigaoctets"),
+        ("téraoctet", "téraoctets"),
+        ("pétaoctet", "pétaoctets"),
+        ("exaoctet", "exaoctets"),
+        ("kibioctet", "kibioctets"),
+        ("mébioctet", "mébioctets"),
+        ("gibioctet", "gibioctets"),
+        ("tébioctet", "tébioctets"),
+        ("pébioctet", "pébioctets"),
+        ("exbioctet", "exbioctets"),
+        ("nanoseconde", "nanosecondes"),
+        ("microseconde", "microsecondes"),
+        ("milliseconde", "millisecondes"),
+        ("seconde", "secondes"),
+        ("minute", "minutes"),
+        ("heure", "heures"),
+        ("jour", "jours"),
+        ("semaine", "semaines"),
+    ],
+    plural: plural_french,
+};
+
+const JA: Table = Table {
+    language: "ja",
+    decimal: '.',
+    grouping: ',',
+    separator: "",
+    symbols: [
+        "B",
+        "kB",
+        "MB",
+        "GB",
+        "TB",
+        "PB",
+        "EB",
+        "KiB",
+        "MiB",
+        "GiB",
+        "TiB",
+        "PiB",
+        "EiB",
+        "ナノ秒",
+        "マイクロ秒",
+        "ミリ秒",
+        "秒",
+        "分",
+        "時間",
+        "日",
+        "週",
+    ],
+    names: [
+        ("バイト", "バイト"),
+        ("キロバイト", "キロバイト"),
+        ("メガバイト", "メガバイト"),
+        ("ギガバイト", "ギガバイト"),
+        ("テラバイト", "テラバイト"),
+        ("ペタバイト", "ペタバイト"),
+        ("エクサバイト", "エクサバイト"),
+        ("キビバイト", "キビバイト"),
+        ("メビバイト", "メビバイト"),
+        ("ギビバイト", "ギビバイト"),
+        ("テビバイト", "テビバイト"),
+        ("ペビバイト", "ペビバイト"),
+        ("エクスビバイト", "エクスビバイト"),
+        ("ナノ秒", "ナノ秒"),
+        ("マイクロ秒", "マイクロ秒"),
+        ("ミリ秒", "ミリ秒"),
+        ("秒", "秒"),
+        ("分", "分"),
+        ("時間", "時間"),
+        ("日", "日"),
+        ("週間", "週間"),
+    ],
+    plural: plural_none,
+};
+
+/// number formats a number with the decimal separator of a locale.
+fn number<L: Locale + ?Sized>(n: impl ToString, locale: &L) -> String {
+    n.to_string()
+        .replace('.', locale.decimal_separator().encode_utf8(&mut [0; 4]))
+}
+
+fn short<L: Locale + ?Sized>(n: impl ToString, unit: Unit, locale: &L) -> String {
+    format!(
+        "{}{}{}",
+        number(n, locale),
+        locale.separator(),
+        locale.symbol(unit)
+    )
+}
+
+fn long<L: Locale + ?Sized>(n: f64, unit: Unit, locale: &L) -> String {
+    format!(
+        "{} {}",
+        number(n, locale),
+        locale.name(unit, locale.plural(n))
+    )
+}
+
+/// bytes is `bytes::bytes` in a locale.
+///
+/// bytes(82854982, &Builtin::Fr) -> 82,9 Mo
+#[must_use]
+pub fn bytes<L: Locale + ?Sized>(s: usize, locale: &L) -> String {
+    let (val, e) = crate::bytes::humanate(s as u128, 1000.0);
+    short(val, SI_UNITS[e], locale)
+}
+
+/// ibytes is `bytes::ibytes` in a locale.
+///
+/// ibytes(82854982, &Builtin::De) -> 79 MiB
+#[must_use]
+pub fn ibytes<L: Locale + ?Sized>(s: usize, locale: &L) -> String {
+    let (val, e) = crate::bytes::humanate(s as u128, 1024.0);
+    short(val, IEC_UNITS[e], locale)
+}
+
+/// bytes_long spells out the unit of `bytes`.
+///
+/// bytes_long(1500000000, &Builtin::En) -> 1.5 gigabytes
+#[must_use]
+pub fn bytes_long<L: Locale + ?Sized>(s: usize, locale: &L) -> String {
+    let (val, e) = crate::bytes::humanate(s as u128, 1000.0);
+    long(val, SI_UNITS[e], locale)
+}
+
+/// ibytes_long spells out the unit of `ibytes`.
+///
+/// ibytes_long(1024, &Builtin::De) -> 1 Kibibyte
+#[must_use]
+pub fn ibytes_long<L: Locale + ?Sized>(s: usize, locale: &L) -> String {
+    let (val, e) = crate::bytes::humanate(s as u128, 1024.0);
+    long(val, IEC_UNITS[e], locale)
+}
+
+const SECOND: u128 = 1_000_000_000;
+
+const HOUR: u128 = 3600 * SECOND;
+
+const TIME_UNITS: [(Unit, u128); 8] = [
+    (Unit::Week, 7 * 24 * HOUR),
+    (Unit::Day, 24 * HOUR),
+    (Unit::Hour, HOUR),
+    (Unit::Minute, 60 * SECOND),
+    (Unit::Second, SECOND),
+    (Unit::Millisecond, 1_000_000),
+    (Unit::Microsecond, 1_000),
+    (Unit::Nanosecond, 1),
+];
+
+/// duration is `duration::duration` in a locale, the parts are the same.
+///
+/// duration(&Duration::from_millis(5400500), &Builtin::De) -> 1 Std. 30 Min. 0,5 Sek.
+#[must_use]
+pub fn duration<L: Locale + ?Sized>(d: &Duration, locale: &L) -> String {
+    let u = d.as_nanos();
+    if u == 0 {
+        return short(0, Unit::Second, locale);
+    }
+
+    // Less than a second uses a single smaller unit, like 1.2ms
+    if u < SECOND {
+        let (unit, scale) = TIME_UNITS[5..]
+            .iter()
+            .find(|(_, scale)| u >= *scale)
+            .copied()
+            .unwrap_or((Unit::Nanosecond, 1));
+        return short(decimal(u, scale), unit, locale);
+    }
+
+    let minute = 60 * SECOND;
+    let parts = [
+        (u / (7 * 24 * HOUR), Unit::Week),
+        (u / (24 * HOUR) % 7, Unit::Day),
+        (u / HOUR % 24, Unit::Hour),
+        (u / minute % 60, Unit::Minute),
+        (u % minute, Unit::Second),
+    ];
+
+    parts
+        .into_iter()
+        .filter(|(n, _)| *n != 0)
+        .map(|(n, unit)| match unit {
+            Unit::Second => short(decimal(n, SECOND), unit, locale),
+            _ => short(n, unit, locale),
+        })
+        .collect::<Vec<_>>()
+        .join(locale.separator())
+}
+
+/// duration_long spells out a duration in its largest unit, rounded to one
+/// decimal.
+///
+/// duration_long(&Duration::from_secs(5400), &Builtin::Ja) -> 1.5 時間
+#[must_use]
+pub fn duration_long<L: Locale + ?Sized>(d: &Duration, locale: &L) -> String {
+    let u = d.as_nanos();
+    let (unit, scale) = TIME_UNITS
+        .iter()
+        .find(|(_, scale)| u >= *scale)
+        .copied()
+        .unwrap_or((Unit::Second, SECOND));
+
+    let val = (u as f64 / scale as f64 * 10.0 + 0.5).floor() / 10.0;
+    long(val, unit, locale)
+}
+
+/// parse_bytes is `bytes::parse_bytes` in a locale, numbers use the decimal
+/// and grouping separators of the locale, units are either localized or the
+/// ones `bytes::parse_bytes` accepts, as are its "e[+-]N" and "^N" exponents.
+/// Numbers like "1.500" or "1,500" are rejected in every locale, as they read
+/// differently in others.
+///
+/// parse_bytes("1,5 Go", &Builtin::Fr) -> Ok(1500000000)
+/// parse_bytes("1.500,5 KiB", &Builtin::De) -> Ok(1536512)
+/// parse_bytes("2,5e3 MB", &Builtin::De) -> Ok(2500000000)
+///
+/// # Errors
+///
+/// Return `Error` if the input is not valid, or is ambiguous.
+pub fn parse_bytes<L: Locale + ?Sized>(input: &str, locale: &L) -> Result<usize, Error> {
+    let (number, rest) = scan_number(input.trim(), locale);
+    let mut canonical = canonical_number(number, locale)?;
+    canonical.push_str(rest);
+
+    // The exponent follows the canonical number, as `bytes::parse_bytes` reads it
+    let (number, unit) = crate::bytes::split_number(&canonical);
+    let scale = byte_scale(unit.trim(), locale)?;
+
+    crate::bytes::number_size(number, scale).ok_or_else(|| Error::TooLarge {
+        input: input.to_string(),
+    })
+}
+
+/// parse_duration is `duration::parse_duration` in a locale, with the same
+/// rules for numbers and units as `parse_bytes`.
+///
+/// parse_duration("2,5 Std.", &Builtin::De) -> Ok(9000s)
+/// parse_duration("1 Stunde 30 Minuten", &Builtin::De) -> Ok(5400s)
+///
+/// # Errors
+///
+/// Return `Error` if the input is not valid, or is ambiguous.
+pub fn parse_duration<L: Locale + ?Sized>(input: &str, locale: &L) -> Result<Duration, Error> {
+    let mut s = input.trim();
+    let mut canonical = String::with_capacity(s.len());
+    if let Some(rest) = s.strip_prefix(['-', '+']) {
+        canonical.push_str(&s[..1]);
+        s = rest;
+    }
+
+    while !s.is_empty() {
+        let (number, rest) = scan_number(s, locale);
+        canonical.push_str(&canonical_number(number, locale)?);
+
+        let end = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        canonical.push_str(time_unit(rest[..end].trim(), locale)?);
+        s = &rest[end..];
+    }
+
+    crate::duration::parse_duration(&canonical).map_err(Error::Duration)
+}
+
+fn is_grouping<L: Locale + ?Sized>(c: char, locale: &L) -> bool {
+    match locale.grouping_separator() {
+        Some(' ') => matches!(c, ' ' | '\u{a0}' | '\u{202f}'),
+        Some(g) => c == g,
+        None => false,
+    }
+}
+
+/// scan_number splits off the leading number, any '.' or ',' is included so
+/// it can be checked for ambiguity.
+fn scan_number<'a, L: Locale + ?Sized>(s: &'a str, locale: &L) -> (&'a str, &'a str) {
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next_digit = chars.peek().is_some_and(|(_, c)| c.is_ascii_digit());
+        let separator = c == locale.decimal_separator() || matches!(c, '.' | ',');
+        if !(c.is_ascii_digit() || separator || (next_digit && is_grouping(c, locale))) {
+            return s.split_at(i);
+        }
+    }
+
+    (s, "")
+}
+
+/// canonical_number checks a number against the locale and rewrites it with
+/// '.' as the decimal separator and without grouping.
+fn canonical_number<L: Locale + ?Sized>(number: &str, locale: &L) -> Result<String, Error> {
+    let invalid = || Error::InvalidNumber {
+        number: number.to_string(),
+    };
+
+    // A single '.' or ',' followed by three digits, like "1.500", a leading
+    // "0" can't be a thousands group so "0.500" is a decimal
+    let separators: Vec<_> = number
+        .match_indices(|c: char| !c.is_ascii_digit())
+        .collect();
+    if let [(i, "." | ",")] = separators[..]
+        && (1..=3).contains(&i)
+        && &number[..i] != "0"
+        && number.len() - i - 1 == 3
+    {
+        return Err(Error::AmbiguousNumber {
+            number: number.to_string(),
+        });
+    }
+
+    let (int, frac) = match number.split_once(locale.decimal_separator()) {
+        Some((int, frac)) => (int, Some(frac)),
+        None => (number, None),
+    };
+
+    let groups: Vec<_> = int.split(|c| is_grouping(c, locale)).collect();
+    let grouped = groups.len() > 1
+        && (1..=3).contains(&groups[0].len())
+        && !groups[0].starts_with('0')
+        && groups[1..].iter().all(|g| g.len() == 3);
+    let digits = |s: &str| s.bytes().all(|c| c.is_ascii_digit());
+    if (groups.len() > 1 && !grouped)
+        || !groups.iter().all(|g| digits(g))
+        || !frac.is_none_or(digits)
+        || !number.contains(|c: char| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+
+    let mut canonical = groups.concat();
+    if let Some(frac) = frac {
+        canonical.push('.');
+        canonical.push_str(frac);
+    }
+
+    Ok(canonical)
+}
+
+/// matches reports whether text is a localized name of unit, ignoring case
+/// and a trailing '.'.
+fn matches<L: Locale + ?Sized>(text: &str, unit: Unit, locale: &L) -> bool {
+    let fold = |s: &str| s.strip_suffix('.').unwrap_or(s).to_lowercase();
+    let text = fold(text);
+
+    [
+        locale.symbol(unit),
+        locale.name(unit, Plural::One),
+        locale.name(unit, Plural::Other),
+    ]
+    .into_iter()
+    .any(|name| !text.is_empty() && fold(name) == text)
+}
+
+fn byte_scale<L: Locale + ?Sized>(unit: &str, locale: &L) -> Result<usize, Error> {
+    let mut scales: Vec<_> = SI_UNITS
+        .iter()
+        .chain(&IEC_UNITS)
+        .filter(|u| matches(unit, **u, locale))
+        .filter_map(|u| crate::bytes::unit_scale(EN.symbols[*u as usize]))
+        .chain(crate::bytes::unit_scale(unit))
+        .collect();
+    scales.sort_unstable();
+    scales.dedup();
+
+    match scales[..] {
+        [scale] => Ok(scale),
+        [] => Err(Error::UnknownUnit {
+            unit: unit.to_string(),
+        }),
+        _ => Err(Error::AmbiguousUnit {
+            unit: unit.to_string(),
+        }),
+    }
+}
+
+/// time_unit returns the unit `duration::parse_duration` accepts for a
+/// localized unit.
+fn time_unit<L: Locale + ?Sized>(unit: &str, locale: &L) -> Result<&'static str, Error> {
+    let standard = match unit {
+        "" => return Ok(""),
+        "ns" => Some("ns"),
+        "us" | "µs" | "μs" => Some("us"),
+        "ms" => Some("ms"),
+        "s" => Some("s"),
+        "m" => Some("m"),
+        "h" => Some("h"),
+        "d" => Some("d"),
+        "w" => Some("w"),
+        _ => None,
+    };
+
+    let mut units: Vec<_> = TIME_UNITS
+        .iter()
+        .filter(|(u, _)| matches(unit, *u, locale))
+        .map(|(u, _)| EN.symbols[*u as usize])
+        .chain(standard)
+        .collect();
+    units.sort_unstable();
+    units.dedup();
+
+    match units[..] {
+        [unit] => Ok(unit),
+        [] => Err(Error::UnknownUnit {
+            unit: unit.to_string(),
+        }),
+        _ => Err(Error::AmbiguousUnit {
+            unit: unit.to_string(),
+        }),
+    }
+}
+
+/// decimal formats v / scale, where scale is a power of ten, without
+/// trailing zeros.
+fn decimal(v: u128, scale: u128) -> String {
+    let (int, mut frac) = (v / scale, v % scale);
+    if frac == 0 {
+        return int.to_string();
+    }
+
+    let mut digits = scale.ilog10() as usize;
+    while frac.is_multiple_of(10) {
+        frac /= 10;
+        digits -= 1;
+    }
+
+    format!("{}.{:0digits$}", int, frac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_tag() {
+        assert_eq!(Builtin::from_tag("de"), Some(Builtin::De));
+        assert_eq!(Builtin::from_tag("fr-CA"), Some(Builtin::Fr));
+        assert_eq!(Builtin::from_tag("ja_JP.UTF-8"), Some(Builtin::Ja));
+        assert_eq!(Builtin::from_tag("EN"), Some(Builtin::En));
+        assert_eq!(Builtin::from_tag("pt-BR"), None);
+        assert_eq!(Builtin::from_tag(""), None);
+    }
+
+    #[test]
+    fn bytes() {
+        let tests = [
+            (Builtin::En, 1500000000, "1.5GB", "1.5 gigabytes"),
+            (Builtin::De, 1500000000, "1,5 GB", "1,5 Gigabyte"),
+            (Builtin::Fr, 1500000000, "1,5 Go", "1,5 gigaoctet"),
+            (Builtin::Fr, 2000000000, "2 Go", "2 gigaoctets"),
+            (Builtin::Ja, 1500000000, "1.5GB", "1.5 ギガバイト"),
+            (Builtin::En, 1, "1B", "1 byte"),
+            (Builtin::En, 0, "0B", "0 bytes"),
+            (Builtin::Fr, 0, "0 o", "0 octet"),
+        ];
+
+        for (locale, size, want, want_long) in tests {
+            assert_eq!(super::bytes(size, &locale), want, "{locale:?} {size}");
+            assert_eq!(bytes_long(size, &locale), want_long, "{locale:?} {size}");
+        }
+
+        assert_eq!(ibytes(82854982, &Builtin::De), "79 MiB");
+        assert_eq!(ibytes_long(1024, &Builtin::De), "1 Kibibyte");
+        assert_eq!(ibytes(1536, &Builtin::Fr), "1,5 Kio");
+
+        // English is the same as the unlocalized formatters
+        for size in [0, 9, 10, 999, 1000, 82854982, 1 << 40, usize::MAX] {
+            assert_eq!(super::bytes(size, &Builtin::En), crate::bytes::bytes(size));
+            assert_eq!(ibytes(size, &Builtin::En), crate::bytes::ibytes(size));
+        }
+    }
+
+    #[test]
+    fn duration() {
+        let tests = [
+            (Builtin::En, 5400, "1h30m", "1.5 hours"),
+            (Builtin::De, 5400, "1 Std. 30 Min.", "1,5 Stunden"),
+            (Builtin::Fr, 5400, "1 h 30 min", "1,5 heure"),
+            (Builtin::Ja, 5400, "1時間30分", "1.5 時間"),
+            (Builtin::De, 60, "1 Min.", "1 Minute"),
+            (Builtin::Fr, 7200, "2 h", "2 heures"),
+            (Builtin::En, 129_600, "1d12h", "1.5 days"),
+            (Builtin::De, 1_209_600, "2 Wo.", "2 Wochen"),
+            (Builtin::Ja, 691_200, "1週1日", "1.1 週間"),
+        ];
+
+        for (locale, secs, want, want_long) in tests {
+            let d = Duration::from_secs(secs);
+            assert_eq!(super::duration(&d, &locale), want, "{locale:?} {secs}");
+            assert_eq!(duration_long(&d, &locale), want_long, "{locale:?} {secs}");
+        }
+
+        let d = Duration::from_millis(5400500);
+        assert_eq!(super::duration(&d, &Builtin::De), "1 Std. 30 Min. 0,5 Sek.");
+        assert_eq!(super::duration(&d, &Builtin::Ja), "1時間30分0.5秒");
+        let d = Duration::from_micros(1500);
+        assert_eq!(super::duration(&d, &Builtin::Fr), "1,5 ms");
+        assert_eq!(duration_long(&d, &Builtin::En), "1.5 milliseconds");
+        assert_eq!(duration_long(&Duration::ZERO, &Builtin::En), "0 seconds");
+
+        // English is the same as the unlocalized formatter
+        for nanos in [
+            0,
+            1,
+            1_500,
+            999_999_999,
+            1_000_000_000,
+            60_000_000_001,
+            3_600_000_000_000,
+            5_400_500_000_000,
+            u64::MAX,
+        ] {
+            let d = Duration::from_nanos(nanos);
+            assert_eq!(
+                super::duration(&d, &Builtin::En),
+                crate::duration::duration(&d)
+            );
+        }
+        let d = Duration::MAX;
+        assert_eq!(
+            super::duration(&d, &Builtin::En),
+            crate::duration::duration(&d)
+        );
+    }
+
+    #[test]
+    fn parse_bytes() {
+        let tests = [
+            (Builtin::De, "1,5 GB", 1500000000),
+            (Builtin::De, "1.500,5 KiB", 1536512),
+            (Builtin::De, "2 Gigabyte", 2000000000),
+            (Builtin::Fr, "1,5 Go", 1500000000),
+            (Builtin::Fr, "1 500 000 o", 1500000),
+            (Builtin::Fr, "1\u{202f}500,25 Mio", 1573126144),
+            (Builtin::Fr, "2 gigaoctets", 2000000000),
+            (Builtin::Fr, "42 MiB", 44040192),
+            (Builtin::En, "1,500,000", 1500000),
+            (Builtin::En, "1.5 gigabytes", 1500000000),
+            (Builtin::Ja, "1.5 ギガバイト", 1500000000),
+            (Builtin::Ja, "1,234.5kB", 1234500),
+            (Builtin::De, "2,5e3 MB", 2500000000),
+            (Builtin::Fr, "1,5E-3 Go", 1500000),
+            (Builtin::En, "1.5e+3 KiB", 1536000),
+            (Builtin::De, "2^10 KiB", 1048576),
+            (Builtin::En, "1e", 1000000000000000000),
+            (Builtin::En, "0.500 GB", 500000000),
+            (Builtin::De, "0,500 GB", 500000000),
+        ];
+
+        for (locale, input, want) in tests {
+            let got = super::parse_bytes(input, &locale);
+            assert_eq!(got, Ok(want), "{locale:?} {input}");
+        }
+
+        let tests = [
+            (
+                Builtin::De,
+                "1.500 GB",
+                "ambiguous number \"1.500\", the separator could be decimal or grouping",
+            ),
+            (
+                Builtin::En,
+                "1,500 GB",
+                "ambiguous number \"1,500\", the separator could be decimal or grouping",
+            ),
+            (
+                Builtin::Fr,
+                "1,500 Go",
+                "ambiguous number \"1,500\", the separator could be decimal or grouping",
+            ),
+            (Builtin::De, "1.5 GB", "invalid number \"1.5\""),
+            (Builtin::En, "1,5 GB", "invalid number \"1,5\""),
+            (Builtin::En, "1234,567", "invalid number \"1234,567\""),
+            (Builtin::De, "1,5,0 GB", "invalid number \"1,5,0\""),
+            (Builtin::De, "0.500 GB", "invalid number \"0.500\""),
+            (Builtin::En, "0,500 GB", "invalid number \"0,500\""),
+            (Builtin::Fr, "1,5 Xo", "unknown unit \"Xo\""),
+            (Builtin::Fr, "Go", "invalid number \"\""),
+            (Builtin::En, "16 EiB", "too large \"16 EiB\""),
+            (Builtin::En, "2^64 B", "too large \"2^64 B\""),
+            (Builtin::De, "1,5^2 GB", "unknown unit \"^2 GB\""),
+        ];
+
+        for (locale, input, want) in tests {
+            let err = super::parse_bytes(input, &locale).unwrap_err();
+            assert_eq!(err.to_string(), want, "{locale:?} {input}");
+        }
+    }
+
+    #[test]
+    fn parse_duration() {
+        let tests = [
+            (Builtin::De, "2,5h", 9_000_000),
+            (Builtin::De, "2,5 Std", 9_000_000),
+            (Builtin::De, "1 Std. 30 Min.", 5_400_000),
+            (Builtin::De, "1 Stunde 30 Minuten 0,5 Sekunden", 5_400_500),
+            (Builtin::Fr, "1 h 30 min", 5_400_000),
+            (Builtin::Fr, "1,5 heure", 5_400_000),
+            (Builtin::Ja, "1時間30分", 5_400_000),
+            (Builtin::Ja, "1.5 時間", 5_400_000),
+            (Builtin::En, "1.5 hours", 5_400_000),
+            (Builtin::En, "1h30m", 5_400_000),
+            (Builtin::De, "1 Tag 12 Std.", 129_600_000),
+            (Builtin::Fr, "2 semaines", 1_209_600_000),
+            (Builtin::En, "1 day", 86_400_000),
+        ];
+
+        for (locale, input, want) in tests {
+            let got = super::parse_duration(input, &locale);
+            assert_eq!(got, Ok(Duration::from_millis(want)), "{locale:?} {input}");
+        }
+
+        let tests = [
+            (
+                Builtin::De,
+                "1.500 Std.",
+                "ambiguous number \"1.500\", the separator could be decimal or grouping",
+            ),
+            (Builtin::De, "1.5h", "invalid number \"1.5\""),
+            (Builtin::De, "1 Jahr", "unknown unit \"Jahr\""),
+            (Builtin::En, "1 year", "unknown unit \"year\""),
+            (Builtin::De, "-1 Std.", "invalid duration"),
+            (Builtin::Fr, "30", "missing unit"),
+        ];
+
+        for (locale, input, want) in tests {
+            let err = super::parse_duration(input, &locale).unwrap_err();
+            assert_eq!(err.to_string(), want, "{locale:?} {input}");
+        }
+
+        let got = super::parse_duration("0", &Builtin::Fr);
+        assert_eq!(got, Ok(Duration::ZERO));
+
+        // Localized output parses back
+        let d = Duration::from_millis(5400500);
+        for locale in [Builtin::En, Builtin::De, Builtin::Fr, Builtin::Ja] {
+            let got = super::parse_duration(&super::duration(&d, &locale), &locale);
+            assert_eq!(got, Ok(d), "{locale:?}");
+        }
+    }
+
+    #[test]
+    fn custom() {
+        struct Pirate;
+
+        impl Locale for Pirate {
+            fn decimal_separator(&self) -> char {
+                '·'
+            }
+
+            fn separator(&self) -> &str {
+                " "
+            }
+
+            fn symbol(&self, unit: Unit) -> &str {
+                match unit {
+                    Unit::Second => "m",
+                    _ => Builtin::En.symbol(unit),
+                }
+            }
+
+            fn name(&self, unit: Unit, plural: Plural) -> &str {
+                match (unit, plural) {
+                    (Unit::Hour, Plural::One) => "bell",
+                    (Unit::Hour, Plural::Other) => "bells",
+                    _ => Builtin::En.name(unit, plural),
+                }
+            }
+
+            fn plural(&self, n: f64) -> Plural {
+                Builtin::En.plural(n)
+            }
+        }
+
+        let locale: &dyn Locale = &Pirate;
+        assert_eq!(super::bytes(1500, locale), "1·5 kB");
+        assert_eq!(duration_long(&Duration::from_secs(3600), locale), "1 bell");
+        assert_eq!(locale.name(Unit::Hour, Plural::Other), "bells");
+
+        assert_eq!(super::parse_bytes("1·5 kB", locale), Ok(1500));
+        assert_eq!(
+            super::parse_duration("2 bells", locale),
+            Ok(Duration::from_secs(7200))
+        );
+        let err = super::parse_duration("1 m", locale).unwrap_err();
+        assert_eq!(err.to_string(), "ambiguous unit \"m\"");
+    }
+}
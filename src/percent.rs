@@ -0,0 +1,221 @@
+use std::fmt::{Display, Formatter};
+use std::num::ParseFloatError;
+
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error<'a> {
+    ParseFloat(ParseFloatError),
+    UnknownUnit { unit: &'a str },
+    MissingPercent { input: &'a str },
+}
+
+impl<'a> std::error::Error for Error<'a> {}
+
+impl<'a> Display for Error<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::ParseFloat(err) => {
+                write!(f, "parse float part failed, {}", err)
+            }
+            Error::UnknownUnit { unit } => write!(f, "unknown unit \"{}\"", unit),
+            Error::MissingPercent { input } => write!(
+                f,
+                "ratio \"{}\" is above 1, add a \"%\" for a percentage",
+                input
+            ),
+        }
+    }
+}
+
+impl<'a> From<ParseFloatError> for Error<'a> {
+    fn from(err: ParseFloatError) -> Self {
+        Self::ParseFloat(err)
+    }
+}
+
+/// Ratio is a fraction of a whole, `1.0` is 100%.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct Ratio(f64);
+
+impl Ratio {
+    /// Creates a ratio from a fraction, e.g. `0.85`.
+    #[must_use]
+    pub const fn new(ratio: f64) -> Self {
+        Self(ratio)
+    }
+
+    /// Creates a ratio from a percentage, e.g. `85.0`.
+    #[must_use]
+    pub const fn from_percent(percent: f64) -> Self {
+        Self(percent / 100.0)
+    }
+
+    /// Returns the ratio of `part` to `total`, a zero `total` yields a zero ratio.
+    #[must_use]
+    pub fn between(part: usize, total: usize) -> Self {
+        if total == 0 {
+            return Self(0.0);
+        }
+
+        Self(part as f64 / total as f64)
+    }
+
+    #[must_use]
+    pub const fn as_f64(&self) -> f64 {
+        self.0
+    }
+
+    #[must_use]
+    pub const fn as_percent(&self) -> f64 {
+        self.0 * 100.0
+    }
+}
+
+impl Display for Ratio {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let precision = f.precision().unwrap_or(0);
+        write!(f, "{:.*}%", precision, self.as_percent())
+    }
+}
+
+/// percent produces a human-readable representation of a ratio, with
+/// `precision` digits after the decimal point.
+///
+/// percent(Ratio::new(0.8534), 1) -> 85.3%
+#[must_use]
+pub fn percent(ratio: Ratio, precision: usize) -> String {
+    format!("{:.*}%", precision, ratio.as_percent())
+}
+
+/// `parse_percent` parses a percentage, or a plain ratio between 0 and 1.
+/// Bare numbers above 1 are rejected, "85" is more likely a percentage with
+/// the "%" forgotten than a ratio of 8500%.
+///
+/// parse_percent("85%") -> Ok(Ratio::new(0.85))
+/// parse_percent("85 percent") -> Ok(Ratio::new(0.85))
+/// parse_percent("0.85") -> Ok(Ratio::new(0.85))
+/// parse_percent("85") -> Err(Error::MissingPercent)
+///
+/// # Errors
+///
+/// Return `Error::MissingPercent` if a plain ratio is above 1, or `Error` if
+/// the input is not valid.
+pub fn parse_percent(input: &str) -> Result<Ratio, Error<'_>> {
+    let input = input.trim();
+
+    let mut last_digit = 0;
+    for ch in input.chars() {
+        if !(ch.is_ascii_digit() || ch == '.') {
+            break;
+        }
+
+        last_digit += 1;
+    }
+
+    let flt = input[..last_digit].parse::<f64>()?;
+    let unit = input[last_digit..].trim();
+
+    if unit.is_empty() && flt > 1.0 {
+        Err(Error::MissingPercent { input })
+    } else if unit.is_empty() {
+        Ok(Ratio::new(flt))
+    } else if unit == "%" || unit.eq_ignore_ascii_case("percent") {
+        Ok(Ratio::from_percent(flt))
+    } else {
+        Err(Error::UnknownUnit { unit })
+    }
+}
+
+/// usage produces a "used/total (percent)" string from two byte counts,
+/// sizes are formatted with `ibytes`.
+///
+/// usage(44040192, 104857600, 0) -> 42MiB/100MiB (42%)
+#[cfg(feature = "bytes")]
+#[must_use]
+pub fn usage(used: usize, total: usize, precision: usize) -> String {
+    use crate::bytes::ibytes;
+
+    format!(
+        "{}/{} ({})",
+        ibytes(used),
+        ibytes(total),
+        percent(Ratio::between(used, total), precision)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let tests = [
+            ("85%", 0.85),
+            ("85 %", 0.85),
+            ("85.5%", 0.855),
+            ("0.85", 0.85),
+            ("1", 1.0),
+            ("85 percent", 0.85),
+            ("85percent", 0.85),
+            ("85 PERCENT", 0.85),
+            ("150%", 1.5),
+            (" 85% ", 0.85),
+            ("0%", 0.0),
+        ];
+
+        for (input, want) in tests {
+            let got = parse_percent(input).unwrap();
+            assert!(
+                (got.as_f64() - want).abs() < 1e-9,
+                "input: {input}, got: {got:?}"
+            );
+        }
+
+        for input in ["", "%", "85 pct", "eighty%", "-5%"] {
+            assert!(parse_percent(input).is_err(), "input: {input}");
+        }
+
+        for input in ["85", "1.5", " 100 "] {
+            let err = parse_percent(input).unwrap_err();
+            assert!(
+                matches!(err, Error::MissingPercent { .. }),
+                "input: {input}, err: {err}"
+            );
+        }
+        assert_eq!(
+            parse_percent("85").unwrap_err().to_string(),
+            "ratio \"85\" is above 1, add a \"%\" for a percentage"
+        );
+    }
+
+    #[test]
+    fn stringify() {
+        let tests = [
+            (percent(Ratio::new(0.85), 0), "85%"),
+            (percent(Ratio::new(0.8534), 1), "85.3%"),
+            (percent(Ratio::new(0.8534), 2), "85.34%"),
+            (percent(Ratio::new(0.0), 1), "0.0%"),
+            (percent(Ratio::from_percent(150.0), 0), "150%"),
+            (format!("{}", Ratio::new(0.5)), "50%"),
+            (format!("{:.1}", Ratio::new(0.5)), "50.0%"),
+        ];
+
+        for (got, want) in tests {
+            assert_eq!(got, want);
+        }
+    }
+
+    #[test]
+    fn between() {
+        assert_eq!(Ratio::between(1, 4), Ratio::new(0.25));
+        assert_eq!(Ratio::between(1, 0), Ratio::new(0.0));
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn usage_string() {
+        assert_eq!(usage(44040192, 104857600, 0), "42MiB/100MiB (42%)");
+        assert_eq!(usage(512, 2048, 1), "512B/2KiB (25.0%)");
+        assert_eq!(usage(0, 0, 0), "0B/0B (0%)");
+    }
+}
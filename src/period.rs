@@ -0,0 +1,430 @@
+// Calendar periods like "1y6mo", resolved against a date
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+
+use crate::duration::{duration, parse_duration};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    InvalidPeriod,
+    Duration(crate::duration::Error),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::InvalidPeriod => write!(f, "invalid period"),
+            Error::Duration(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<crate::duration::Error> for Error {
+    fn from(err: crate::duration::Error) -> Self {
+        Self::Duration(err)
+    }
+}
+
+/// Date is a day in the proleptic Gregorian calendar, without a time zone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Date {
+    year: i32,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    /// new returns the date, or None if the month or day does not exist.
+    ///
+    /// Date::new(2023, 2, 29) -> None
+    #[must_use]
+    pub const fn new(year: i32, month: u8, day: u8) -> Option<Self> {
+        if month < 1 || month > 12 || day < 1 || day > days_in_month(year, month) {
+            return None;
+        }
+        Some(Self { year, month, day })
+    }
+
+    #[must_use]
+    pub const fn year(&self) -> i32 {
+        self.year
+    }
+
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+
+    /// add_months moves the date by whole months, the day is clamped to the
+    /// end of a shorter month, e.g. Jan 31 + 1 month is Feb 28.
+    fn add_months(self, months: i64) -> Option<Self> {
+        let months = i64::from(self.year) * 12 + i64::from(self.month - 1) + months;
+        let year = i32::try_from(months.div_euclid(12)).ok()?;
+        let month = months.rem_euclid(12) as u8 + 1;
+        let day = self.day.min(days_in_month(year, month));
+        Some(Self { year, month, day })
+    }
+
+    /// days returns the number of days since 1970-01-01.
+    fn days(self) -> i64 {
+        // From Howard Hinnant's days_from_civil
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+/// Formats the date as "2024-02-29".
+impl Display for Date {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+const fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Period is a number of years and months, which vary in length, followed by
+/// an exact duration.
+///
+/// See also: `parse_period`
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Period {
+    years: u32,
+    months: u32,
+    time: Duration,
+}
+
+impl Period {
+    #[must_use]
+    pub const fn new(years: u32, months: u32, time: Duration) -> Self {
+        Self {
+            years,
+            months,
+            time,
+        }
+    }
+
+    #[must_use]
+    pub const fn years(&self) -> u32 {
+        self.years
+    }
+
+    #[must_use]
+    pub const fn months(&self) -> u32 {
+        self.months
+    }
+
+    /// time returns the exact part of the period, e.g. "2w" of "3mo2w".
+    #[must_use]
+    pub const fn time(&self) -> Duration {
+        self.time
+    }
+
+    fn total_months(&self) -> i64 {
+        i64::from(self.years) * 12 + i64::from(self.months)
+    }
+
+    /// resolve returns the length of the period starting at `start`, or None
+    /// if it ends outside the calendar or overflows a `Duration`.
+    ///
+    /// parse_period("1mo")?.resolve(Date::new(2024, 2, 1)?) -> Some(29 days)
+    #[must_use]
+    pub fn resolve(&self, start: Date) -> Option<Duration> {
+        let end = start.add_months(self.total_months())?;
+        self.exact(end.days() - start.days())
+    }
+
+    /// resolve_before returns the length of the period ending at `end`, e.g.
+    /// how far back a "6mo" retention reaches from today.
+    #[must_use]
+    pub fn resolve_before(&self, end: Date) -> Option<Duration> {
+        let start = end.add_months(-self.total_months())?;
+        self.exact(end.days() - start.days())
+    }
+
+    fn exact(&self, days: i64) -> Option<Duration> {
+        let secs = u64::try_from(days).ok()?.checked_mul(SECONDS_PER_DAY)?;
+        Duration::from_secs(secs).checked_add(self.time)
+    }
+
+    /// iso returns the period in ISO 8601 form, with the time in whole days,
+    /// hours, minutes and seconds.
+    ///
+    /// parse_period("1y6mo36h")?.iso() -> P1Y6M1DT12H
+    #[must_use]
+    pub fn iso(&self) -> String {
+        let mut s = String::from("P");
+        if self.years > 0 {
+            s += &format!("{}Y", self.years);
+        }
+        if self.months > 0 {
+            s += &format!("{}M", self.months);
+        }
+
+        let secs = self.time.as_secs();
+        let (days, hours) = (secs / SECONDS_PER_DAY, secs % SECONDS_PER_DAY / 3600);
+        let (minutes, seconds) = (secs % 3600 / 60, secs % 60);
+        let nanos = self.time.subsec_nanos();
+        if days > 0 {
+            s += &format!("{}D", days);
+        }
+        if hours > 0 || minutes > 0 || seconds > 0 || nanos > 0 {
+            s.push('T');
+        }
+        if hours > 0 {
+            s += &format!("{}H", hours);
+        }
+        if minutes > 0 {
+            s += &format!("{}M", minutes);
+        }
+        if nanos > 0 {
+            let fraction = format!("{:09}", nanos);
+            s += &format!("{}.{}S", seconds, fraction.trim_end_matches('0'));
+        } else if seconds > 0 {
+            s += &format!("{}S", seconds);
+        }
+
+        if s == "P" {
+            s += "0D";
+        }
+        s
+    }
+}
+
+/// Formats the period as "1y6mo12h", the exact part with `duration`. The
+/// zero period is "0s".
+impl Display for Period {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.years > 0 {
+            write!(f, "{}y", self.years)?;
+        }
+        if self.months > 0 {
+            write!(f, "{}mo", self.months)?;
+        }
+        if !self.time.is_zero() || (self.years == 0 && self.months == 0) {
+            write!(f, "{}", duration(&self.time))?;
+        }
+        Ok(())
+    }
+}
+
+/// parse_period parses years "y" and months "mo" followed by a duration
+/// accepted by `parse_duration`, e.g. "1y6mo" or "3mo2w", or an ISO 8601
+/// period like "P1Y6M" or "P3M2WT12H".
+///
+/// # Errors
+///
+/// Return `Error::Duration` if the exact part is not a valid duration, or
+/// `Error::InvalidPeriod` if the input is malformed.
+pub fn parse_period(text: &str) -> Result<Period, Error> {
+    let text = text.trim();
+    if let Some(iso) = text.strip_prefix('P') {
+        return parse_iso(iso);
+    } else if text.is_empty() {
+        return Err(Error::InvalidPeriod);
+    }
+
+    let mut period = Period::default();
+    let mut rest = text;
+    loop {
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let (number, unit) = rest.split_at(digits);
+        let field = if let Some(unit) = unit.strip_prefix("mo") {
+            rest = unit;
+            &mut period.months
+        } else if let Some(unit) = unit.strip_prefix('y') {
+            rest = unit;
+            &mut period.years
+        } else {
+            break;
+        };
+        if number.is_empty() || *field != 0 {
+            return Err(Error::InvalidPeriod);
+        }
+        *field = number.parse().map_err(|_| Error::InvalidPeriod)?;
+    }
+
+    if !rest.is_empty() {
+        period.time = parse_duration(rest)?;
+    }
+    Ok(period)
+}
+
+/// parse_iso parses an ISO 8601 period after the 'P'.
+fn parse_iso(iso: &str) -> Result<Period, Error> {
+    let (date, time) = match iso.split_once('T') {
+        Some((_, "")) => return Err(Error::InvalidPeriod),
+        Some((date, time)) => (date, time),
+        None => (iso, ""),
+    };
+    if date.is_empty() && time.is_empty() {
+        return Err(Error::InvalidPeriod);
+    }
+
+    let mut period = Period::default();
+    let mut exact = String::new();
+    for (is_date, part, designators) in [(true, date, "YMWD"), (false, time, "HMS")] {
+        let mut designators = designators.chars();
+        let mut rest = part;
+        while !rest.is_empty() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .ok_or(Error::InvalidPeriod)?;
+            let (number, designator) = (&rest[..end], rest[end..].chars().next().unwrap());
+            if number.is_empty() || !designators.any(|d| d == designator) {
+                return Err(Error::InvalidPeriod);
+            }
+            rest = &rest[end + 1..];
+
+            match (is_date, designator) {
+                (true, 'Y') => period.years = number.parse().map_err(|_| Error::InvalidPeriod)?,
+                (true, 'M') => period.months = number.parse().map_err(|_| Error::InvalidPeriod)?,
+                _ => {
+                    exact += number;
+                    exact.push(designator.to_ascii_lowercase());
+                }
+            }
+        }
+    }
+
+    if !exact.is_empty() {
+        period.time = parse_duration(&exact)?;
+    }
+    Ok(period)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY);
+
+    fn date(year: i32, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            Date::new(2024, 2, 29).map(|d| d.to_string()).as_deref(),
+            Some("2024-02-29")
+        );
+        assert_eq!(Date::new(2023, 2, 29), None);
+        assert_eq!(Date::new(1900, 2, 29), None);
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert_eq!(Date::new(2024, 13, 1), None);
+        assert_eq!(Date::new(2024, 4, 31), None);
+        assert_eq!(Date::new(2024, 1, 0), None);
+
+        assert_eq!(date(1970, 1, 1).days(), 0);
+        assert_eq!(date(2000, 3, 1).days(), 11_017);
+        assert_eq!(date(1969, 12, 31).days(), -1);
+        assert_eq!(date(2024, 1, 31).add_months(1), Some(date(2024, 2, 29)));
+        assert_eq!(date(2024, 3, 31).add_months(-13), Some(date(2023, 2, 28)));
+        assert_eq!(date(i32::MAX, 12, 1).add_months(1), None);
+    }
+
+    #[test]
+    fn parse() {
+        let secs = Duration::from_secs;
+        let tests = [
+            ("1y6mo", Period::new(1, 6, Duration::ZERO), "1y6mo", "P1Y6M"),
+            ("3mo2w", Period::new(0, 3, 14 * DAY), "3mo2w", "P3M14D"),
+            ("6mo", Period::new(0, 6, Duration::ZERO), "6mo", "P6M"),
+            ("1y", Period::new(1, 0, Duration::ZERO), "1y", "P1Y"),
+            (
+                "2y1h30m",
+                Period::new(2, 0, secs(5400)),
+                "2y1h30m",
+                "P2YT1H30M",
+            ),
+            ("90m", Period::new(0, 0, secs(5400)), "1h30m", "PT1H30M"),
+            ("P1Y6M", Period::new(1, 6, Duration::ZERO), "1y6mo", "P1Y6M"),
+            ("P3M2W", Period::new(0, 3, 14 * DAY), "3mo2w", "P3M14D"),
+            (
+                "P1DT12H",
+                Period::new(0, 0, secs(129_600)),
+                "1d12h",
+                "P1DT12H",
+            ),
+            (
+                "PT0.5S",
+                Period::new(0, 0, Duration::from_millis(500)),
+                "500ms",
+                "PT0.5S",
+            ),
+            ("P0D", Period::default(), "0s", "P0D"),
+        ];
+
+        for (input, period, display, iso) in tests {
+            assert_eq!(parse_period(input), Ok(period), "input: {input}");
+            assert_eq!(period.to_string(), display, "input: {input}");
+            assert_eq!(period.iso(), iso, "input: {input}");
+            assert_eq!(parse_period(iso), Ok(period), "input: {input}");
+        }
+
+        let tests = [
+            ("", Error::InvalidPeriod),
+            ("y", Error::InvalidPeriod),
+            ("1y1y", Error::InvalidPeriod),
+            ("99999999999y", Error::InvalidPeriod),
+            ("P", Error::InvalidPeriod),
+            ("PT", Error::InvalidPeriod),
+            ("P1M1Y", Error::InvalidPeriod),
+            ("P1H", Error::InvalidPeriod),
+            ("P1", Error::InvalidPeriod),
+            ("P1MT1M1M", Error::InvalidPeriod),
+            ("P1.5Y", Error::InvalidPeriod),
+            ("1.5y", Error::Duration(crate::duration::Error::UnknownUnit)),
+            ("1y6", Error::Duration(crate::duration::Error::MissingUnit)),
+        ];
+        for (input, want) in tests {
+            assert_eq!(parse_period(input), Err(want), "input: {input}");
+        }
+    }
+
+    #[test]
+    fn resolve() {
+        let month = parse_period("1mo").unwrap();
+        assert_eq!(month.resolve(date(2024, 2, 1)), Some(29 * DAY));
+        assert_eq!(month.resolve(date(2023, 2, 1)), Some(28 * DAY));
+        assert_eq!(month.resolve(date(2024, 1, 31)), Some(29 * DAY));
+        assert_eq!(month.resolve_before(date(2024, 3, 31)), Some(31 * DAY));
+
+        let year = parse_period("1y").unwrap();
+        assert_eq!(year.resolve(date(2024, 1, 1)), Some(366 * DAY));
+        assert_eq!(year.resolve(date(2024, 3, 1)), Some(365 * DAY));
+        assert_eq!(year.resolve_before(date(2024, 3, 1)), Some(366 * DAY));
+
+        let period = parse_period("P1Y6MT1H").unwrap();
+        let want = (366 + 181) * DAY + Duration::from_secs(3600);
+        assert_eq!(period.resolve(date(2024, 1, 1)), Some(want));
+
+        let huge = Period::new(u32::MAX, 0, Duration::ZERO);
+        assert_eq!(huge.resolve(date(2024, 1, 1)), None);
+        let max = Period::new(1, 0, Duration::MAX);
+        assert_eq!(max.resolve(date(2024, 1, 1)), None);
+    }
+}